@@ -0,0 +1,91 @@
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ActivityKind {
+    AddTask,
+    EditTask,
+    CompleteTask,
+    ReopenTask,
+    DeleteTask,
+    RenameTask,
+    /// A task moved to another project, the subject being the task.
+    MoveTask,
+    /// A change made from the task window taken back, the subject describing it.
+    UndoChange,
+    RenameProject,
+    ArchiveProject,
+    UnarchiveProject,
+    /// The project removed, its activity log being kept with this entry last.
+    DeleteProject,
+}
+
+/// One change of a project, as recorded in its activity log.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    pub at: DateTime<Utc>,
+    pub user: String,
+    pub kind: ActivityKind,
+    /// Name of the task, or of the project for project wide changes.
+    pub subject: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// The user running the program, as told by the environment.
+pub fn get_user_name() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| String::from("unknown"))
+}
+
+impl ActivityEntry {
+    pub fn new(
+        kind: ActivityKind,
+        subject: String,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> ActivityEntry {
+        ActivityEntry {
+            at: Utc::now(),
+            user: get_user_name(),
+            kind,
+            subject,
+            before,
+            after,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let action = match self.kind {
+            ActivityKind::AddTask => "added task",
+            ActivityKind::EditTask => "edited task",
+            ActivityKind::CompleteTask => "completed task",
+            ActivityKind::ReopenTask => "reopened task",
+            ActivityKind::DeleteTask => "deleted task",
+            ActivityKind::RenameTask => "renamed task",
+            ActivityKind::MoveTask => "moved task",
+            ActivityKind::UndoChange => "undid",
+            ActivityKind::RenameProject => "renamed project",
+            ActivityKind::ArchiveProject => "archived project",
+            ActivityKind::UnarchiveProject => "unarchived project",
+            ActivityKind::DeleteProject => "deleted project",
+        };
+        let mut description = format!(
+            "{} {} {} \"{}\"",
+            self.at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            self.user,
+            action,
+            self.subject
+        );
+        match (&self.before, &self.after) {
+            (Some(before), Some(after)) => {
+                description.push_str(format!(": \"{}\" -> \"{}\"", before, after).as_str())
+            }
+            (None, Some(after)) => description.push_str(format!(": \"{}\"", after).as_str()),
+            (Some(before), None) => description.push_str(format!(", was \"{}\"", before).as_str()),
+            (None, None) => {}
+        }
+        description
+    }
+}
@@ -0,0 +1,252 @@
+use crate::formats::ics::CalendarComponent;
+use crate::formats::{self, Format};
+use crate::storage::StorageKind;
+use chrono::NaiveDate;
+use std::path::PathBuf;
+
+pub static USAGE: &str = "Usage:
+  project_manager [path]                       Open the project manager
+  project_manager migrate --to <sqlite|json> [--force] [path]
+                                               Convert the working folder to another storage,
+                                               --force overwrites the projects already there
+  project_manager import [--format <format>] [--map <Column=field,...>] <file> [path]
+                                               Add the tasks of a file to the projects,
+                                               --map chooses the field of each csv column
+  project_manager export --format <format> [--output <file>] [--project <name,...>]
+                       [--component <todo|event>] [--updated-since <YYYY-MM-DD>]
+                       [--completed-since <YYYY-MM-DD>] [path]
+                                               Write the projects in another format,
+                                               --component chooses the ics entries,
+                                               the --*-since options keep the recent tasks
+  project_manager sync <file.md> [--watch] [path]
+                                               Update the projects to match a markdown checklist,
+                                               again on every change of the file with --watch
+  project_manager log <project> [path]         Print the activity log of a project
+  project_manager search <text> [--archived] [path]
+                                               List the tasks mentioning a text, also in the
+                                               archived projects with --archived
+
+Formats: todotxt, markdown, csv, ics, taskwarrior
+Csv fields: project, name, description, status, time_spent, estimate, priority, tags, due_date,
+            created_at, updated_at, completed_at, notes";
+
+pub enum Command {
+    Interactive,
+    Migrate {
+        to: StorageKind,
+        force: bool,
+    },
+    Import {
+        file: PathBuf,
+        format: Format,
+        column_mapping: Option<String>,
+    },
+    Export {
+        format: Format,
+        output: Option<PathBuf>,
+        project: Option<String>,
+        updated_since: Option<NaiveDate>,
+        completed_since: Option<NaiveDate>,
+    },
+    Sync {
+        file: PathBuf,
+        watch: bool,
+    },
+    Log {
+        project: String,
+    },
+    Search {
+        query: String,
+        include_archived: bool,
+    },
+}
+
+pub struct Arguments {
+    pub command: Command,
+    pub base_path: Option<PathBuf>,
+}
+
+/// Options that do not take a value.
+static FLAGS: [&str; 3] = ["watch", "force", "archived"];
+
+/// `--name value` options, `--flag` flags and positional values of a sub command.
+struct SubCommandArguments {
+    options: Vec<(String, String)>,
+    flags: Vec<String>,
+    positionals: Vec<String>,
+}
+
+impl SubCommandArguments {
+    fn parse(args: &[String]) -> Result<SubCommandArguments, String> {
+        let mut parsed = SubCommandArguments {
+            options: vec![],
+            flags: vec![],
+            positionals: vec![],
+        };
+        let mut remaining = args.iter();
+        while let Some(arg) = remaining.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if FLAGS.contains(&name) => parsed.flags.push(String::from(name)),
+                Some(name) => match remaining.next() {
+                    Some(value) => parsed.options.push((String::from(name), value.clone())),
+                    None => return Err(format!("Missing value after {}", arg)),
+                },
+                None => parsed.positionals.push(arg.clone()),
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn get_option(&self, name: &str) -> Option<String> {
+        self.options
+            .iter()
+            .find(|(option_name, _)| option_name == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn has_flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    fn get_positional(&self, index: usize) -> Option<String> {
+        self.positionals.get(index).cloned()
+    }
+}
+
+fn parse_storage_kind(value: Option<String>) -> Result<StorageKind, String> {
+    match value.as_deref() {
+        Some("sqlite") => Ok(StorageKind::Sqlite),
+        Some("json") => Ok(StorageKind::Json),
+        Some(other) => Err(format!("Unknown storage: {}", other)),
+        None => Err(String::from("migrate requires --to <sqlite|json>")),
+    }
+}
+
+fn parse_format(value: Option<String>) -> Result<Format, String> {
+    match value {
+        Some(name) => match formats::get_format_from_name(name.as_str()) {
+            Some(format) => Ok(format),
+            None => Err(format!("Unknown format: {}", name)),
+        },
+        None => Err(String::from("Missing --format")),
+    }
+}
+
+fn parse_date(option_name: &str, value: Option<String>) -> Result<Option<NaiveDate>, String> {
+    match value {
+        Some(date) => match NaiveDate::parse_from_str(date.as_str(), "%Y-%m-%d") {
+            Ok(date) => Ok(Some(date)),
+            Err(_) => Err(format!(
+                "--{} expects a YYYY-MM-DD date, got {}",
+                option_name, date
+            )),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Parses the program arguments, without the executable name.
+pub fn parse_arguments(args: Vec<String>) -> Result<Arguments, String> {
+    let sub_command_arguments = SubCommandArguments::parse(args.get(1..).unwrap_or(&[]))?;
+    match args.first().map(|a| a.as_str()) {
+        Some("migrate") => Ok(Arguments {
+            command: Command::Migrate {
+                to: parse_storage_kind(sub_command_arguments.get_option("to"))?,
+                force: sub_command_arguments.has_flag("force"),
+            },
+            base_path: sub_command_arguments.get_positional(0).map(PathBuf::from),
+        }),
+        Some("import") => {
+            let file = match sub_command_arguments.get_positional(0) {
+                Some(file) => PathBuf::from(file),
+                None => return Err(String::from("import requires a file")),
+            };
+            let format = match sub_command_arguments.get_option("format") {
+                Some(name) => parse_format(Some(name))?,
+                None => match formats::get_format_from_path(&file) {
+                    Some(format) => format,
+                    None => return Err(String::from("Cannot guess the format, use --format")),
+                },
+            };
+            let column_mapping = sub_command_arguments.get_option("map");
+            if column_mapping.is_some() && !matches!(format, Format::Csv) {
+                return Err(String::from("--map only applies to csv files"));
+            }
+            Ok(Arguments {
+                command: Command::Import {
+                    file,
+                    format,
+                    column_mapping,
+                },
+                base_path: sub_command_arguments.get_positional(1).map(PathBuf::from),
+            })
+        }
+        Some("export") => {
+            let mut format = parse_format(sub_command_arguments.get_option("format"))?;
+            if let Some(component) = sub_command_arguments.get_option("component") {
+                format = match (format, component.as_str()) {
+                    (Format::Ics(_), "todo") => Format::Ics(CalendarComponent::Todo),
+                    (Format::Ics(_), "event") => Format::Ics(CalendarComponent::Event),
+                    (Format::Ics(_), other) => {
+                        return Err(format!("Unknown calendar component: {}", other))
+                    }
+                    _ => return Err(String::from("--component only applies to ics")),
+                };
+            }
+            Ok(Arguments {
+                command: Command::Export {
+                    format,
+                    output: sub_command_arguments
+                        .get_option("output")
+                        .map(PathBuf::from),
+                    project: sub_command_arguments.get_option("project"),
+                    updated_since: parse_date(
+                        "updated-since",
+                        sub_command_arguments.get_option("updated-since"),
+                    )?,
+                    completed_since: parse_date(
+                        "completed-since",
+                        sub_command_arguments.get_option("completed-since"),
+                    )?,
+                },
+                base_path: sub_command_arguments.get_positional(0).map(PathBuf::from),
+            })
+        }
+        Some("sync") => match sub_command_arguments.get_positional(0) {
+            Some(file) => Ok(Arguments {
+                command: Command::Sync {
+                    file: PathBuf::from(file),
+                    watch: sub_command_arguments.has_flag("watch"),
+                },
+                base_path: sub_command_arguments.get_positional(1).map(PathBuf::from),
+            }),
+            None => Err(String::from("sync requires a markdown file")),
+        },
+        Some("log") => match sub_command_arguments.get_positional(0) {
+            Some(project) => Ok(Arguments {
+                command: Command::Log { project },
+                base_path: sub_command_arguments.get_positional(1).map(PathBuf::from),
+            }),
+            None => Err(String::from("log requires a project name")),
+        },
+        Some("search") => match sub_command_arguments.get_positional(0) {
+            Some(query) => Ok(Arguments {
+                command: Command::Search {
+                    query,
+                    include_archived: sub_command_arguments.has_flag("archived"),
+                },
+                base_path: sub_command_arguments.get_positional(1).map(PathBuf::from),
+            }),
+            None => Err(String::from("search requires a text to look for")),
+        },
+        Some("-h") | Some("--help") => Err(String::new()),
+        Some(path) => Ok(Arguments {
+            command: Command::Interactive,
+            base_path: Some(PathBuf::from(path)),
+        }),
+        None => Ok(Arguments {
+            command: Command::Interactive,
+            base_path: None,
+        }),
+    }
+}
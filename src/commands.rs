@@ -0,0 +1,206 @@
+use crate::formats::{self, markdown, Format};
+use crate::storage::{self, Storage};
+use crate::structure::{Project, Task};
+use chrono::NaiveDate;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Loads the projects named in the comma separated `project_names`, or every project.
+fn load_selected_projects(
+    storage: &dyn Storage,
+    project_names: Option<String>,
+) -> Result<Vec<Project>, Error> {
+    match project_names {
+        Some(names) => names
+            .split(',')
+            .map(|name| storage.load_project(String::from(name.trim())))
+            .collect(),
+        None => storage.load_projects(),
+    }
+}
+
+pub fn import(
+    working_folder: PathBuf,
+    file: PathBuf,
+    format: Format,
+    column_mapping: Option<String>,
+) -> Result<String, Error> {
+    let storage = storage::open_storage(working_folder)?;
+    formats::import_file(storage.as_ref(), &file, &format, column_mapping.as_deref())
+}
+
+/// Writes the export to `output`, or returns it to be printed when no output file is given.
+pub fn export(
+    working_folder: PathBuf,
+    format: Format,
+    output: Option<PathBuf>,
+    project_names: Option<String>,
+    updated_since: Option<NaiveDate>,
+    completed_since: Option<NaiveDate>,
+) -> Result<String, Error> {
+    let storage = storage::open_storage(working_folder)?;
+    let projects = load_selected_projects(storage.as_ref(), project_names)?;
+    let exported_projects = formats::filter_tasks(&projects, updated_since, completed_since);
+    let result = match output {
+        Some(output_path) => formats::export_file(&exported_projects, &output_path, &format)?,
+        None => formats::export_projects(&exported_projects, &format),
+    };
+    formats::save_task_ids(storage.as_ref(), &projects, &format)?;
+    Ok(result)
+}
+
+fn sync_markdown_file(storage: &dyn Storage, file: &Path) -> Result<String, Error> {
+    let content = std::fs::read_to_string(file)?;
+    let source_projects =
+        markdown::import_projects(content.as_str(), formats::get_default_project_name(file)?);
+    let mut synced_names: Vec<String> = vec![];
+    for source in source_projects {
+        let mut project = match storage.load_project(source.name.clone()) {
+            Ok(project) => project,
+            Err(_) => Project::new(source.name.clone()),
+        };
+        let changes = markdown::sync_project(&mut project, source);
+        storage.save_project(&project)?;
+        for change in changes.iter() {
+            storage.append_activity(project.name.clone(), change)?;
+        }
+        synced_names.push(project.name);
+    }
+    Ok(format!("Synced {}", synced_names.join(", ")))
+}
+
+fn get_modification_time(file: &Path) -> Result<SystemTime, Error> {
+    std::fs::metadata(file)?.modified()
+}
+
+/// Syncs the projects of a markdown checklist, then with `watch` keeps syncing them every
+/// time the file is modified.
+pub fn sync(working_folder: PathBuf, file: PathBuf, watch: bool) -> Result<String, Error> {
+    let storage = storage::open_storage(working_folder)?;
+    let message = sync_markdown_file(storage.as_ref(), &file)?;
+    if !watch {
+        return Ok(message);
+    }
+    println!("{}", message);
+    println!(
+        "Watching {} for changes, Ctrl-C to stop",
+        file.to_str().unwrap()
+    );
+    let mut last_modification = get_modification_time(&file)?;
+    loop {
+        std::thread::sleep(Duration::from_secs(1));
+        let modification = get_modification_time(&file)?;
+        if modification != last_modification {
+            last_modification = modification;
+            println!("{}", sync_markdown_file(storage.as_ref(), &file)?);
+        }
+    }
+}
+
+/// The activity log of the project, one entry per line, also once the project is deleted.
+pub fn log(working_folder: PathBuf, project_name: String) -> Result<String, Error> {
+    let storage = storage::open_storage(working_folder)?;
+    let entries = storage.load_activity(project_name.clone())?;
+    match storage.load_project(project_name.clone()) {
+        Err(e) if e.kind() == ErrorKind::NotFound && entries.is_empty() => {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("No project named {}", project_name),
+            ))
+        }
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    if entries.is_empty() {
+        return Ok(format!("No activity recorded for {}", project_name));
+    }
+    Ok(entries
+        .iter()
+        .map(|entry| entry.describe())
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+fn task_mentions(task: &Task, query: &str) -> bool {
+    task.name.to_lowercase().contains(query)
+        || task.description.to_lowercase().contains(query)
+        || task
+            .tags
+            .iter()
+            .any(|tag| tag.to_lowercase().contains(query))
+        || task
+            .notes
+            .iter()
+            .any(|note| note.text.to_lowercase().contains(query))
+}
+
+/// The tasks whose name, description, tags or notes contain `query`, whatever the case, as
+/// `Project: task` lines. Archived projects are only searched with `include_archived`.
+fn find_tasks(projects: &[Project], query: &str, include_archived: bool) -> Vec<String> {
+    let query = query.to_lowercase();
+    let mut found: Vec<String> = vec![];
+    for project in projects.iter().filter(|p| include_archived || !p.archived) {
+        let project_label = match project.archived {
+            true => format!("{} (archived)", project.name),
+            false => project.name.clone(),
+        };
+        for (task, completed) in project
+            .active_tasks
+            .iter()
+            .map(|t| (t, false))
+            .chain(project.completed_tasks.iter().map(|t| (t, true)))
+            .filter(|(t, _)| task_mentions(t, query.as_str()))
+        {
+            found.push(format!(
+                "{}: {}{}",
+                project_label,
+                task.name,
+                match completed {
+                    true => " (completed)",
+                    false => "",
+                }
+            ));
+        }
+    }
+    found
+}
+
+pub fn search(
+    working_folder: PathBuf,
+    query: String,
+    include_archived: bool,
+) -> Result<String, Error> {
+    let storage = storage::open_storage(working_folder)?;
+    let found = find_tasks(&storage.load_projects()?, query.as_str(), include_archived);
+    if found.is_empty() {
+        return Ok(format!("No task mentions {}", query));
+    }
+    Ok(found.join("\n"))
+}
+
+#[test]
+fn search_skips_archived_projects() {
+    let mut current = Project::new(String::from("Website"));
+    current
+        .active_tasks
+        .push(Task::new(String::from("Fix the Menu"), String::new()));
+    let mut archived = Project::new(String::from("Old website"));
+    archived.archived = true;
+    let mut task = Task::new(String::from("Logo"), String::from("Match the menu"));
+    task.tags.push(String::from("design"));
+    archived.completed_tasks.push(task);
+    let projects = vec![current, archived];
+    assert_eq!(
+        find_tasks(&projects, "menu", false),
+        vec!["Website: Fix the Menu"]
+    );
+    assert_eq!(
+        find_tasks(&projects, "menu", true),
+        vec![
+            "Website: Fix the Menu",
+            "Old website (archived): Logo (completed)"
+        ]
+    );
+    assert!(find_tasks(&projects, "design", false).is_empty());
+}
@@ -0,0 +1,235 @@
+use crate::structure::Project;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Where a task lives and what it waits for, enough to walk dependencies across projects.
+pub struct TaskNode {
+    pub project: String,
+    pub name: String,
+    pub completed: bool,
+    pub depends_on: Vec<Uuid>,
+}
+
+/// Dependencies between the tasks of every project, indexed by task id.
+#[derive(Default)]
+pub struct DependencyGraph {
+    nodes: HashMap<Uuid, TaskNode>,
+}
+
+impl DependencyGraph {
+    pub fn from_projects(projects: &[Project]) -> DependencyGraph {
+        let mut graph = DependencyGraph::default();
+        for project in projects.iter() {
+            graph.add_project(project);
+        }
+        graph
+    }
+
+    /// Adds the tasks of the project, replacing those already known from an older version of it.
+    pub fn add_project(&mut self, project: &Project) {
+        self.nodes.retain(|_, node| node.project != project.name);
+        let task_lists = [
+            (&project.active_tasks, false),
+            (&project.completed_tasks, true),
+        ];
+        for (tasks, completed) in task_lists.iter() {
+            for task in tasks.iter() {
+                self.nodes.insert(
+                    task.id,
+                    TaskNode {
+                        project: project.name.clone(),
+                        name: task.name.clone(),
+                        completed: *completed,
+                        depends_on: task.depends_on.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// The dependencies that are not completed yet. Dependencies on deleted tasks do not block.
+    pub fn get_open_dependencies(&self, depends_on: &[Uuid]) -> Vec<Uuid> {
+        depends_on
+            .iter()
+            .filter(|id| self.nodes.get(id).map(|node| !node.completed) == Some(true))
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_blocked(&self, depends_on: &[Uuid]) -> bool {
+        !self.get_open_dependencies(depends_on).is_empty()
+    }
+
+    /// The active tasks that were only waiting for `id`, once it is completed.
+    pub fn get_unblocked_by(&self, id: &Uuid) -> Vec<Uuid> {
+        self.get_dependents(id)
+            .into_iter()
+            .filter(|dependent| match self.nodes.get(dependent) {
+                Some(node) => !node.completed && !self.is_blocked(&node.depends_on),
+                None => false,
+            })
+            .collect()
+    }
+
+    /// The tasks that directly depend on `id`.
+    pub fn get_dependents(&self, id: &Uuid) -> Vec<Uuid> {
+        let mut dependents: Vec<Uuid> = self
+            .nodes
+            .iter()
+            .filter(|(_, node)| node.depends_on.contains(id))
+            .map(|(dependent_id, _)| *dependent_id)
+            .collect();
+        dependents.sort_by_key(|dependent_id| self.describe_node(dependent_id));
+        dependents
+    }
+
+    /// Whether `task` would end up depending on itself by depending on `dependencies`.
+    pub fn creates_cycle(&self, task: &Uuid, dependencies: &[Uuid]) -> bool {
+        let mut to_visit: Vec<Uuid> = dependencies.to_vec();
+        let mut visited: Vec<Uuid> = vec![];
+        while let Some(id) = to_visit.pop() {
+            if id == *task {
+                return true;
+            }
+            if visited.contains(&id) {
+                continue;
+            }
+            visited.push(id);
+            if let Some(node) = self.nodes.get(&id) {
+                to_visit.extend(node.depends_on.iter().cloned());
+            }
+        }
+        false
+    }
+
+    /// Finds a task from `project/name`, a task id, or a name in `current_project`.
+    pub fn find_task(&self, reference: &str, current_project: &str) -> Result<Uuid, String> {
+        let reference = reference.trim();
+        if let Ok(id) = Uuid::parse_str(reference) {
+            return match self.nodes.contains_key(&id) {
+                true => Ok(id),
+                false => Err(format!("No task with id {}", id)),
+            };
+        }
+        let (project, name) = match reference.split_once('/') {
+            Some((project, name)) => (project.trim(), name.trim()),
+            None => (current_project, reference),
+        };
+        let matches: Vec<&Uuid> = self
+            .nodes
+            .iter()
+            .filter(|(_, node)| node.project == project && node.name == name)
+            .map(|(id, _)| id)
+            .collect();
+        match matches.as_slice() {
+            [id] => Ok(**id),
+            [] => Err(format!("No task named {} in {}", name, project)),
+            _ => Err(format!(
+                "Several tasks are named {} in {}, use its id",
+                name, project
+            )),
+        }
+    }
+
+    /// How a dependency is written in the dependency input, the project being left out
+    /// for tasks of `current_project`.
+    pub fn get_reference(&self, id: &Uuid, current_project: &str) -> String {
+        match self.nodes.get(id) {
+            Some(node) if node.project == current_project => node.name.clone(),
+            Some(node) => format!("{}/{}", node.project, node.name),
+            None => id.to_string(),
+        }
+    }
+
+    pub fn describe_node(&self, id: &Uuid) -> String {
+        match self.nodes.get(id) {
+            Some(node) => format!(
+                "[{}] {} ({})",
+                match node.completed {
+                    true => "x",
+                    false => " ",
+                },
+                node.name,
+                node.project
+            ),
+            None => format!("[?] missing task {}", id),
+        }
+    }
+
+    fn add_dependency_lines(
+        &self,
+        id: &Uuid,
+        depth: usize,
+        path: &mut Vec<Uuid>,
+        lines: &mut Vec<String>,
+    ) {
+        let node = match self.nodes.get(id) {
+            Some(node) => node,
+            None => return,
+        };
+        for dependency in node.depends_on.iter() {
+            lines.push(format!(
+                "{}{}",
+                "  ".repeat(depth),
+                self.describe_node(dependency)
+            ));
+            // Guards against cycles written by hand in the project files.
+            if !path.contains(dependency) {
+                path.push(*dependency);
+                self.add_dependency_lines(dependency, depth + 1, path, lines);
+                path.pop();
+            }
+        }
+    }
+
+    /// The tasks the task waits for, as an indented tree, followed by the tasks waiting for it.
+    pub fn describe_graph(&self, id: &Uuid) -> String {
+        let mut lines = vec![self.describe_node(id), String::from("Depends on:")];
+        let dependency_start = lines.len();
+        self.add_dependency_lines(id, 1, &mut vec![*id], &mut lines);
+        if lines.len() == dependency_start {
+            lines.push(String::from("  nothing"));
+        }
+        lines.push(String::from("Blocks:"));
+        let dependents = self.get_dependents(id);
+        if dependents.is_empty() {
+            lines.push(String::from("  nothing"));
+        }
+        for dependent in dependents.iter() {
+            lines.push(format!("  {}", self.describe_node(dependent)));
+        }
+        lines.join("\n")
+    }
+}
+
+#[test]
+fn blocked_tasks_and_cycles() {
+    use crate::structure::Task;
+    let mut design = Task::new(String::from("Design"), String::new());
+    let mut build = Task::new(String::from("Build"), String::new());
+    let ship = Task::new(String::from("Ship"), String::new());
+    build.depends_on = vec![design.id];
+    let mut release = Project::new(String::from("Release"));
+    release.active_tasks = vec![design.clone(), build.clone()];
+    let mut launch = Project::new(String::from("Launch"));
+    launch.active_tasks = vec![ship.clone()];
+    let graph = DependencyGraph::from_projects(&[release.clone(), launch]);
+
+    assert!(graph.is_blocked(&build.depends_on));
+    assert!(!graph.is_blocked(&design.depends_on));
+    assert!(graph.creates_cycle(&design.id, &[build.id]));
+    assert!(!graph.creates_cycle(&ship.id, &[build.id]));
+    assert_eq!(graph.find_task("Launch/Ship", "Release"), Ok(ship.id));
+    assert_eq!(graph.find_task("Design", "Release"), Ok(design.id));
+
+    design.mark_completed();
+    release.active_tasks = vec![build.clone()];
+    release.completed_tasks = vec![design];
+    let mut graph = graph;
+    graph.add_project(&release);
+    assert!(!graph.is_blocked(&build.depends_on));
+    assert_eq!(
+        graph.get_unblocked_by(&release.completed_tasks[0].id),
+        vec![build.id]
+    );
+}
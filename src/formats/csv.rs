@@ -0,0 +1,339 @@
+use crate::structure::{Project, Task, TaskNote};
+use chrono::{DateTime, NaiveDate, Utc};
+
+static DATE_FORMAT: &str = "%Y-%m-%d";
+static TAG_SEPARATOR: &str = ";";
+
+/// Fields a csv column can be mapped to, in export order.
+pub static FIELDS: [&str; 13] = [
+    "project",
+    "name",
+    "description",
+    "status",
+    "time_spent",
+    "estimate",
+    "priority",
+    "tags",
+    "due_date",
+    "created_at",
+    "updated_at",
+    "completed_at",
+    "notes",
+];
+
+fn escape_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') || field.contains('\r') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+fn format_timestamp(timestamp: Option<DateTime<Utc>>) -> String {
+    timestamp.map(|t| t.to_rfc3339()).unwrap_or_default()
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.trim())
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Lines of the notes column that are not written as `TaskNote::to_line` does become notes of
+/// the current user.
+fn parse_note(line: &str) -> TaskNote {
+    TaskNote::from_line(line).unwrap_or_else(|| TaskNote::new(String::from(line.trim())))
+}
+
+fn task_to_record(project_name: &str, task: &Task, completed: bool) -> Vec<String> {
+    vec![
+        String::from(project_name),
+        task.name.clone(),
+        task.description.clone(),
+        String::from(match completed {
+            true => "completed",
+            false => "active",
+        }),
+        task.time_spent.to_string(),
+        task.estimate.to_string(),
+        task.priority.map(|p| p.to_string()).unwrap_or_default(),
+        task.tags.join(TAG_SEPARATOR),
+        task.due_date
+            .map(|d| d.format(DATE_FORMAT).to_string())
+            .unwrap_or_default(),
+        format_timestamp(task.created_at),
+        format_timestamp(task.updated_at),
+        format_timestamp(task.completed_at),
+        task.notes
+            .iter()
+            .map(TaskNote::to_line)
+            .collect::<Vec<String>>()
+            .join("\n"),
+    ]
+}
+
+pub fn export_projects(projects: &[Project]) -> String {
+    let mut records: Vec<Vec<String>> = vec![FIELDS.iter().map(|f| String::from(*f)).collect()];
+    for project in projects.iter() {
+        for task in project.active_tasks.iter() {
+            records.push(task_to_record(project.name.as_str(), task, false));
+        }
+        for task in project.completed_tasks.iter() {
+            records.push(task_to_record(project.name.as_str(), task, true));
+        }
+    }
+    let mut content = String::new();
+    for record in records.iter() {
+        let escaped: Vec<String> = record.iter().map(|f| escape_field(f)).collect();
+        content.push_str(escaped.join(",").as_str());
+        content.push_str("\r\n");
+    }
+    content
+}
+
+/// Splits the content in records, handling quoted fields that contain separators or new lines.
+pub fn parse_records(content: &str) -> Vec<Vec<String>> {
+    let mut records: Vec<Vec<String>> = vec![];
+    let mut record: Vec<String> = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut characters = content.chars().peekable();
+    while let Some(character) = characters.next() {
+        match (in_quotes, character) {
+            (true, '"') => {
+                if characters.peek() == Some(&'"') {
+                    field.push('"');
+                    characters.next();
+                } else {
+                    in_quotes = false;
+                }
+            }
+            (true, _) => field.push(character),
+            (false, '"') => in_quotes = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (false, _) => field.push(character),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+        .into_iter()
+        .filter(|r| r.iter().any(|f| !f.trim().is_empty()))
+        .collect()
+}
+
+fn get_default_field(header: &str) -> Option<String> {
+    let normalized = header.trim().to_lowercase().replace(' ', "_");
+    let field = match normalized.as_str() {
+        "task" | "title" | "task_name" => "name",
+        "details" => "description",
+        "state" | "done" | "completed" => "status",
+        "spent" | "time" => "time_spent",
+        "estimation" | "estimated" => "estimate",
+        "due" | "due_on" => "due_date",
+        "created" | "created_on" => "created_at",
+        "updated" | "modified" | "modified_at" => "updated_at",
+        "completed_on" | "done_at" => "completed_at",
+        "tag" | "context" | "contexts" | "labels" => "tags",
+        "note" | "comment" | "comments" => "notes",
+        other => other,
+    };
+    FIELDS
+        .iter()
+        .find(|f| **f == field)
+        .map(|f| String::from(*f))
+}
+
+/// Maps every column of the header to a field, or to `None` when the column is ignored.
+/// `mapping` is a list of `Column=field` overriding the mapping guessed from the column names.
+pub fn get_column_mapping(
+    header: &[String],
+    mapping: Option<&str>,
+) -> Result<Vec<Option<String>>, String> {
+    let mut fields: Vec<Option<String>> = header.iter().map(|h| get_default_field(h)).collect();
+    for entry in mapping
+        .unwrap_or("")
+        .split(',')
+        .filter(|e| !e.trim().is_empty())
+    {
+        let (column, field) = match entry.split_once('=') {
+            Some((column, field)) => (column.trim(), field.trim()),
+            None => return Err(format!("Invalid column mapping: {}", entry)),
+        };
+        let column_index = match header.iter().position(|h| h.trim() == column) {
+            Some(index) => index,
+            None => return Err(format!("No column named {}", column)),
+        };
+        fields[column_index] = match field {
+            "" | "ignore" => None,
+            _ if FIELDS.contains(&field) => Some(String::from(field)),
+            _ => return Err(format!("Unknown field: {}", field)),
+        };
+    }
+    if !fields.iter().any(|f| f.as_deref() == Some("name")) {
+        return Err(String::from("No column is mapped to the task name"));
+    }
+    Ok(fields)
+}
+
+fn describe_column_mapping(header: &[String], fields: &[Option<String>]) -> String {
+    header
+        .iter()
+        .zip(fields.iter())
+        .map(|(column, field)| format!("{} -> {}", column, field.as_deref().unwrap_or("(ignored)")))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// An empty value, or a single letter from `A` to `Z`.
+fn parse_priority(value: &str) -> Result<Option<char>, String> {
+    let mut characters = value.trim().chars();
+    match (characters.next(), characters.next()) {
+        (None, _) => Ok(None),
+        (Some(letter), None) if letter.is_ascii_uppercase() => Ok(Some(letter)),
+        _ => Err(format!(
+            "Invalid priority {}, use a letter from A to Z",
+            value.trim()
+        )),
+    }
+}
+
+fn is_completed_status(status: &str) -> bool {
+    matches!(
+        status.trim().to_lowercase().as_str(),
+        "completed" | "complete" | "done" | "x" | "yes" | "true" | "closed"
+    )
+}
+
+/// Also returns the mapping the columns were read with, as `Column -> field` pairs.
+pub fn import_projects_with_mapping(
+    content: &str,
+    default_project_name: String,
+    mapping: Option<&str>,
+) -> Result<(Vec<Project>, String), String> {
+    let records = parse_records(content);
+    let header = match records.first() {
+        Some(header) => header.clone(),
+        None => return Ok((vec![], String::new())),
+    };
+    let fields = get_column_mapping(&header, mapping)?;
+    let mut projects: Vec<Project> = vec![];
+    for (index, record) in records.iter().enumerate().skip(1) {
+        let mut project_name = default_project_name.clone();
+        let mut task = Task::new(String::new(), String::new());
+        let mut completed = false;
+        for (value, field) in record.iter().zip(fields.iter()) {
+            match field.as_deref() {
+                Some("project") if !value.trim().is_empty() => project_name = value.clone(),
+                Some("name") => task.name = value.clone(),
+                Some("description") => task.description = value.clone(),
+                Some("status") => completed = is_completed_status(value),
+                Some("time_spent") => task.time_spent = value.trim().parse().unwrap_or(0),
+                Some("estimate") => task.estimate = value.trim().parse().unwrap_or(0),
+                Some("priority") => {
+                    task.priority =
+                        parse_priority(value).map_err(|e| format!("Row {}: {}", index + 1, e))?
+                }
+                Some("tags") => {
+                    task.tags = value
+                        .split(TAG_SEPARATOR)
+                        .map(|t| String::from(t.trim()))
+                        .filter(|t| !t.is_empty())
+                        .collect()
+                }
+                Some("due_date") => {
+                    task.due_date = NaiveDate::parse_from_str(value.trim(), DATE_FORMAT).ok()
+                }
+                Some("created_at") => task.created_at = parse_timestamp(value).or(task.created_at),
+                Some("updated_at") => task.updated_at = parse_timestamp(value).or(task.updated_at),
+                Some("completed_at") => task.completed_at = parse_timestamp(value),
+                Some("notes") => {
+                    task.notes = value
+                        .lines()
+                        .filter(|l| !l.trim().is_empty())
+                        .map(parse_note)
+                        .collect()
+                }
+                _ => {}
+            }
+        }
+        if task.name.trim().is_empty() {
+            continue;
+        }
+        let project_index = match projects.iter().position(|p| p.name == project_name) {
+            Some(index) => index,
+            None => {
+                projects.push(Project::new(project_name));
+                projects.len() - 1
+            }
+        };
+        match completed {
+            true => projects[project_index].completed_tasks.push(task),
+            false => projects[project_index].active_tasks.push(task),
+        }
+    }
+    Ok((projects, describe_column_mapping(&header, &fields)))
+}
+
+#[test]
+fn csv_round_trip() {
+    let mut project = Project::new(String::from("Work"));
+    let mut task = Task::new(
+        String::from("Review, then merge"),
+        String::from("Says \"ship it\"\nwhen done"),
+    );
+    task.tags = vec![String::from("code"), String::from("review")];
+    task.estimate = 3;
+    task.notes
+        .push(TaskNote::new(String::from("Approved: ship it")));
+    task.mark_completed();
+    let task_completed_at = task.completed_at;
+    project.completed_tasks.push(task);
+    let exported = export_projects(&[project]);
+    let (imported, mapping) =
+        import_projects_with_mapping(exported.as_str(), String::new(), None).unwrap();
+    assert!(mapping.starts_with("project -> project, name -> name"));
+    let priorities = "name,priority\nShip,A\nPlan,\nReview,high\n";
+    assert_eq!(
+        import_projects_with_mapping(priorities, String::new(), None).err(),
+        Some(String::from(
+            "Row 4: Invalid priority high, use a letter from A to Z"
+        ))
+    );
+    let imported_task = &imported[0].completed_tasks[0];
+    assert_eq!(imported[0].name, "Work");
+    assert_eq!(imported_task.name, "Review, then merge");
+    assert_eq!(imported_task.description, "Says \"ship it\"\nwhen done");
+    assert_eq!(imported_task.tags.len(), 2);
+    assert_eq!(imported_task.estimate, 3);
+    assert_eq!(imported_task.notes[0].text, "Approved: ship it");
+    assert_eq!(
+        imported_task.completed_at.map(|t| t.timestamp()),
+        task_completed_at.map(|t| t.timestamp())
+    );
+}
+
+#[test]
+fn guess_column_mapping() {
+    let header: Vec<String> = ["Title", "Notes", "Details", "Owner"]
+        .iter()
+        .map(|h| String::from(*h))
+        .collect();
+    let fields = get_column_mapping(&header, Some("Owner=ignore")).unwrap();
+    assert_eq!(
+        fields,
+        vec![
+            Some(String::from("name")),
+            Some(String::from("notes")),
+            Some(String::from("description")),
+            None,
+        ]
+    );
+}
@@ -0,0 +1,144 @@
+use crate::structure::{Project, Task};
+use chrono::{Duration, Utc};
+
+pub enum CalendarComponent {
+    Todo,
+    Event,
+}
+
+static DATE_FORMAT: &str = "%Y%m%d";
+
+/// Escapes the characters that have a meaning in iCalendar text values.
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Lines longer than 75 octets are folded, continuation lines starting with a space.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_length = 0;
+    for character in line.chars() {
+        if line_length + character.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(character);
+        line_length += character.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// The UID only depends on the task id so that re-exporting updates the calendar entries.
+pub fn get_task_uid(task: &Task) -> String {
+    format!("{}@project_manager", task.id)
+}
+
+/// iCalendar priorities go from 1 (highest) to 9 (lowest).
+fn get_priority(priority: char) -> u32 {
+    match priority.is_ascii_uppercase() {
+        true => std::cmp::min(priority as u32 - 'A' as u32 + 1, 9),
+        false => 0,
+    }
+}
+
+fn write_entry(
+    lines: &mut Vec<String>,
+    project: &Project,
+    task: &Task,
+    completed: bool,
+    component: &CalendarComponent,
+    timestamp: &str,
+) {
+    let due_date = match task.due_date {
+        Some(due_date) => due_date,
+        None => return,
+    };
+    let component_name = match component {
+        CalendarComponent::Todo => "VTODO",
+        CalendarComponent::Event => "VEVENT",
+    };
+    lines.push(format!("BEGIN:{}", component_name));
+    lines.push(format!("UID:{}", get_task_uid(task)));
+    lines.push(format!("DTSTAMP:{}", timestamp));
+    lines.push(format!("SUMMARY:{}", escape_text(task.name.as_str())));
+    if !task.description.is_empty() {
+        lines.push(format!(
+            "DESCRIPTION:{}",
+            escape_text(task.description.as_str())
+        ));
+    }
+    for note in task.notes.iter() {
+        lines.push(format!("COMMENT:{}", escape_text(note.describe().as_str())));
+    }
+    match component {
+        CalendarComponent::Todo => {
+            lines.push(format!("DUE;VALUE=DATE:{}", due_date.format(DATE_FORMAT)));
+            lines.push(String::from(match completed {
+                true => "STATUS:COMPLETED",
+                false => "STATUS:NEEDS-ACTION",
+            }));
+        }
+        CalendarComponent::Event => {
+            lines.push(format!(
+                "DTSTART;VALUE=DATE:{}",
+                due_date.format(DATE_FORMAT)
+            ));
+            lines.push(format!(
+                "DTEND;VALUE=DATE:{}",
+                (due_date + Duration::days(1)).format(DATE_FORMAT)
+            ));
+            lines.push(String::from("TRANSP:TRANSPARENT"));
+        }
+    }
+    if let Some(priority) = task.priority {
+        lines.push(format!("PRIORITY:{}", get_priority(priority)));
+    }
+    let categories: Vec<String> = std::iter::once(&project.name)
+        .chain(task.tags.iter())
+        .map(|c| escape_text(c))
+        .collect();
+    lines.push(format!("CATEGORIES:{}", categories.join(",")));
+    lines.push(format!("END:{}", component_name));
+}
+
+/// Writes a calendar with an entry for every task that has a due date.
+pub fn export_projects(projects: &[Project], component: &CalendarComponent) -> String {
+    let timestamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines: Vec<String> = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//project_manager//project_manager//EN"),
+        String::from("CALSCALE:GREGORIAN"),
+    ];
+    for project in projects.iter() {
+        for task in project.active_tasks.iter() {
+            write_entry(&mut lines, project, task, false, component, &timestamp);
+        }
+        for task in project.completed_tasks.iter() {
+            write_entry(&mut lines, project, task, true, component, &timestamp);
+        }
+    }
+    lines.push(String::from("END:VCALENDAR"));
+    lines.iter().map(|l| fold_line(l)).collect()
+}
+
+#[test]
+fn ics_export_is_stable() {
+    let mut project = Project::new(String::from("Work"));
+    let mut task = Task::new(String::from("Renew certificate; again"), String::new());
+    task.due_date = chrono::NaiveDate::from_ymd_opt(2022, 5, 1);
+    project.active_tasks.push(task.clone());
+    project
+        .active_tasks
+        .push(Task::new(String::from("No due date"), String::new()));
+    let exported = export_projects(&[project], &CalendarComponent::Todo);
+    assert_eq!(exported.matches("BEGIN:VTODO").count(), 1);
+    assert!(exported.contains(format!("UID:{}\r\n", get_task_uid(&task)).as_str()));
+    assert!(exported.contains("SUMMARY:Renew certificate\\; again\r\n"));
+    assert!(exported.contains("DUE;VALUE=DATE:20220501\r\n"));
+}
@@ -0,0 +1,303 @@
+use crate::activity::{ActivityEntry, ActivityKind};
+use crate::structure::{Project, Task, TaskNote};
+
+/// An item being parsed, kept open until an item of the same or lower indentation closes it.
+struct OpenItem {
+    indent: usize,
+    checked: bool,
+    task: Task,
+}
+
+struct MarkdownParser {
+    projects: Vec<Project>,
+    open_items: Vec<OpenItem>,
+    default_project_name: String,
+}
+
+/// Only checkbox items are tasks, other list items are text like any other line.
+fn parse_list_item(line: &str) -> Option<(bool, String)> {
+    let item = line.trim_start().strip_prefix("- ")?;
+    if let Some(name) = item.strip_prefix("[ ] ") {
+        Some((false, String::from(name.trim())))
+    } else {
+        item.strip_prefix("[x] ")
+            .or_else(|| item.strip_prefix("[X] "))
+            .map(|name| (true, String::from(name.trim())))
+    }
+}
+
+fn append_line(text: &mut String, line: &str) {
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text.push_str(line);
+}
+
+impl MarkdownParser {
+    fn current_project(&mut self) -> &mut Project {
+        if self.projects.is_empty() {
+            let mut project = Project::new(self.default_project_name.clone());
+            project.description = String::new();
+            self.projects.push(project);
+        }
+        self.projects.last_mut().unwrap()
+    }
+
+    fn close_items_from_indent(&mut self, indent: usize) {
+        while self.open_items.last().map(|i| i.indent >= indent) == Some(true) {
+            let closed = self.open_items.pop().unwrap();
+            match self.open_items.last_mut() {
+                Some(parent) => parent.task.sub_tasks.push(closed.task),
+                None => match closed.checked {
+                    true => self.current_project().completed_tasks.push(closed.task),
+                    false => self.current_project().active_tasks.push(closed.task),
+                },
+            }
+        }
+    }
+
+    fn parse_line(&mut self, line: &str) {
+        let indent = line.len() - line.trim_start().len();
+        if let Some(heading) = line.strip_prefix("# ") {
+            self.close_items_from_indent(0);
+            let mut project = Project::new(String::from(heading.trim()));
+            project.description = String::new();
+            self.projects.push(project);
+        } else if let Some((checked, name)) = parse_list_item(line) {
+            self.close_items_from_indent(indent);
+            self.open_items.push(OpenItem {
+                indent,
+                checked,
+                task: Task::new(name, String::new()),
+            });
+        } else if !line.trim().is_empty() {
+            match self.open_items.last_mut() {
+                Some(item) if indent > item.indent => {
+                    match line.trim().strip_prefix("> ").and_then(TaskNote::from_line) {
+                        Some(note) => item.task.notes.push(note),
+                        None => append_line(&mut item.task.description, line.trim()),
+                    }
+                }
+                _ => {
+                    self.close_items_from_indent(0);
+                    append_line(&mut self.current_project().description, line.trim());
+                }
+            }
+        }
+    }
+}
+
+/// Reads `# Project` headings and `- [ ]`/`- [x]` items. Nested items become sub tasks and
+/// indented text under an item becomes its description, or a note when quoted as `> ` followed
+/// by a note line. Items before any heading go to `default_project_name`.
+pub fn import_projects(content: &str, default_project_name: String) -> Vec<Project> {
+    let mut parser = MarkdownParser {
+        projects: vec![],
+        open_items: vec![],
+        default_project_name,
+    };
+    for line in content.lines() {
+        parser.parse_line(line);
+    }
+    parser.close_items_from_indent(0);
+    parser.projects
+}
+
+fn write_task(lines: &mut Vec<String>, task: &Task, checked: bool, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let check_box = match checked {
+        true => "[x]",
+        false => "[ ]",
+    };
+    lines.push(format!("{}- {} {}", indent, check_box, task.name));
+    for description_line in task.description.lines().filter(|l| !l.trim().is_empty()) {
+        lines.push(format!("{}  {}", indent, description_line.trim()));
+    }
+    for note in task.notes.iter() {
+        lines.push(format!("{}  > {}", indent, note.to_line()));
+    }
+    // Sub tasks have no completion state of their own
+    for sub_task in task.sub_tasks.iter() {
+        write_task(lines, sub_task, false, depth + 1);
+    }
+}
+
+pub fn export_project(project: &Project) -> String {
+    let mut lines: Vec<String> = vec![format!("# {}", project.name), String::new()];
+    if !project.description.trim().is_empty() {
+        lines.push(String::from(project.description.trim()));
+        lines.push(String::new());
+    }
+    for task in project.active_tasks.iter() {
+        write_task(&mut lines, task, false, 0);
+    }
+    for task in project.completed_tasks.iter() {
+        write_task(&mut lines, task, true, 0);
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+pub fn export_projects(projects: &[Project]) -> String {
+    projects
+        .iter()
+        .map(export_project)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Keeps the fields markdown does not carry (estimates, tags, due dates...) from the
+/// existing task of the same name, the notes of the file being added to its notes.
+fn merge_task(existing_tasks: &[&Task], source: Task) -> Task {
+    match existing_tasks.iter().find(|t| t.name == source.name) {
+        Some(existing) => {
+            let existing_sub_tasks: Vec<&Task> = existing.sub_tasks.iter().collect();
+            let mut merged = (*existing).clone();
+            if !source.description.is_empty() {
+                merged.description = source.description;
+            }
+            for note in source.notes {
+                if !merged.notes.contains(&note) {
+                    merged.notes.push(note);
+                }
+            }
+            merged.sub_tasks = source
+                .sub_tasks
+                .into_iter()
+                .map(|t| merge_task(&existing_sub_tasks, t))
+                .collect();
+            merged
+        }
+        None => source,
+    }
+}
+
+/// Makes `project` match the checklist of `source`, the markdown file being the reference
+/// for which tasks exist and whether they are completed. Returns the changes made.
+pub fn sync_project(project: &mut Project, source: Project) -> Vec<ActivityEntry> {
+    let existing_tasks: Vec<Task> = project
+        .active_tasks
+        .iter()
+        .chain(project.completed_tasks.iter())
+        .cloned()
+        .collect();
+    let existing_references: Vec<&Task> = existing_tasks.iter().collect();
+    let previously_completed: Vec<String> = project
+        .completed_tasks
+        .iter()
+        .map(|t| t.name.clone())
+        .collect();
+    if !source.description.is_empty() {
+        project.description = source.description;
+    }
+    project.active_tasks = source
+        .active_tasks
+        .into_iter()
+        .map(|t| merge_task(&existing_references, t))
+        .collect();
+    project.completed_tasks = source
+        .completed_tasks
+        .into_iter()
+        .map(|t| merge_task(&existing_references, t))
+        .collect();
+
+    let mut changes: Vec<ActivityEntry> = vec![];
+    for task in project
+        .active_tasks
+        .iter_mut()
+        .chain(project.completed_tasks.iter_mut())
+    {
+        match existing_tasks.iter().find(|t| t.name == task.name) {
+            Some(existing) if existing.description != task.description => {
+                task.mark_updated();
+                changes.push(ActivityEntry::new(
+                    ActivityKind::EditTask,
+                    task.name.clone(),
+                    Some(existing.description.clone()),
+                    Some(task.description.clone()),
+                ));
+            }
+            Some(_) => {}
+            None => changes.push(ActivityEntry::new(
+                ActivityKind::AddTask,
+                task.name.clone(),
+                None,
+                None,
+            )),
+        }
+    }
+    for task in project
+        .completed_tasks
+        .iter_mut()
+        .filter(|t| !previously_completed.contains(&t.name))
+    {
+        task.mark_completed();
+        changes.push(ActivityEntry::new(
+            ActivityKind::CompleteTask,
+            task.name.clone(),
+            None,
+            None,
+        ));
+    }
+    for task in project
+        .active_tasks
+        .iter_mut()
+        .filter(|t| previously_completed.contains(&t.name))
+    {
+        task.mark_reopened();
+        changes.push(ActivityEntry::new(
+            ActivityKind::ReopenTask,
+            task.name.clone(),
+            None,
+            None,
+        ));
+    }
+    for removed in existing_tasks.iter().filter(|existing| {
+        !project
+            .active_tasks
+            .iter()
+            .chain(project.completed_tasks.iter())
+            .any(|t| t.name == existing.name)
+    }) {
+        changes.push(ActivityEntry::new(
+            ActivityKind::DeleteTask,
+            removed.name.clone(),
+            None,
+            None,
+        ));
+    }
+    project.mark_updated();
+    changes
+}
+
+#[test]
+fn markdown_round_trip() {
+    let content = "# Release\n\nSteps for a release\n\n- [ ] Bump version\n  - [ ] Cargo.toml\n    - on main\n- [x] Write changelog\n  Keep it short\n  > 2022-05-02T10:00:00+00:00 ann: Reviewed\n";
+    let projects = import_projects(content, String::from("TODO"));
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].name, "Release");
+    assert_eq!(projects[0].description, "Steps for a release");
+    assert_eq!(projects[0].active_tasks[0].sub_tasks[0].name, "Cargo.toml");
+    assert_eq!(
+        projects[0].active_tasks[0].sub_tasks[0].description,
+        "- on main"
+    );
+    assert_eq!(projects[0].completed_tasks[0].description, "Keep it short");
+    assert_eq!(projects[0].completed_tasks[0].notes[0].author, "ann");
+    assert_eq!(export_projects(&projects), content);
+}
+
+#[test]
+fn sync_reports_changes() {
+    let mut project = import_projects("# Home\n\n- [ ] Paint\n- [ ] Sweep\n", String::new());
+    let source = import_projects("# Home\n\n- [x] Paint\n- [ ] Mow\n", String::new());
+    let changes: Vec<String> = sync_project(&mut project[0], source[0].clone())
+        .iter()
+        .map(|c| format!("{:?} {}", c.kind, c.subject))
+        .collect();
+    assert_eq!(
+        changes,
+        vec!["AddTask Mow", "CompleteTask Paint", "DeleteTask Sweep"]
+    );
+    assert!(project[0].completed_tasks[0].completed_at.is_some());
+}
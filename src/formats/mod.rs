@@ -0,0 +1,266 @@
+pub mod csv;
+pub mod ics;
+pub mod markdown;
+pub mod taskwarrior;
+pub mod todotxt;
+
+use crate::activity::{ActivityEntry, ActivityKind};
+use crate::storage::Storage;
+use crate::structure::{Project, Task};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+pub enum Format {
+    TodoTxt,
+    Markdown,
+    Csv,
+    Ics(ics::CalendarComponent),
+    Taskwarrior,
+}
+
+pub fn get_format_from_name(name: &str) -> Option<Format> {
+    match name {
+        "todotxt" | "todo.txt" => Some(Format::TodoTxt),
+        "markdown" | "md" => Some(Format::Markdown),
+        "csv" => Some(Format::Csv),
+        "ics" | "ical" | "icalendar" => Some(Format::Ics(ics::CalendarComponent::Todo)),
+        "taskwarrior" | "tw" => Some(Format::Taskwarrior),
+        _ => None,
+    }
+}
+
+/// Extension of the files of each format read by `get_format_from_path`, with the name of the
+/// format when the extension does not tell it and whether files of the format can be imported.
+static FILE_EXTENSIONS: &[(&str, Option<&str>, bool)] = &[
+    ("csv", None, true),
+    ("md", None, true),
+    ("ics", None, false),
+    ("json", Some("Taskwarrior"), true),
+    ("txt", Some("todo.txt"), true),
+];
+
+/// The extensions of the formats that can be exported, or imported, as listed to the user:
+/// `.csv, .md or .txt for todo.txt`.
+pub fn describe_file_extensions(importing: bool) -> String {
+    let mut extensions: Vec<String> = FILE_EXTENSIONS
+        .iter()
+        .filter(|(_, _, importable)| *importable || !importing)
+        .map(|(extension, name, _)| match name {
+            Some(name) => format!(".{} for {}", extension, name),
+            None => format!(".{}", extension),
+        })
+        .collect();
+    let last = extensions.pop().unwrap_or_default();
+    match extensions.is_empty() {
+        true => last,
+        false => format!("{} or {}", extensions.join(", "), last),
+    }
+}
+
+pub fn get_format_from_path(path: &Path) -> Option<Format> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("txt") => Some(Format::TodoTxt),
+        Some("md") | Some("markdown") => Some(Format::Markdown),
+        Some("csv") => Some(Format::Csv),
+        Some("ics") => Some(Format::Ics(ics::CalendarComponent::Todo)),
+        Some("json") => Some(Format::Taskwarrior),
+        _ => None,
+    }
+}
+
+pub fn export_projects(projects: &[Project], format: &Format) -> String {
+    match format {
+        Format::TodoTxt => todotxt::export_projects(projects),
+        Format::Markdown => markdown::export_projects(projects),
+        Format::Csv => csv::export_projects(projects),
+        Format::Ics(component) => ics::export_projects(projects, component),
+        Format::Taskwarrior => taskwarrior::export_projects(projects),
+    }
+}
+
+/// `column_mapping` only applies to csv, see `csv::get_column_mapping`.
+pub fn import_projects(
+    content: &str,
+    format: &Format,
+    default_project_name: String,
+    column_mapping: Option<&str>,
+) -> Result<Vec<Project>, Error> {
+    match format {
+        Format::TodoTxt => Ok(todotxt::import_projects(content, default_project_name)),
+        Format::Markdown => Ok(markdown::import_projects(content, default_project_name)),
+        Format::Csv => {
+            csv::import_projects_with_mapping(content, default_project_name, column_mapping)
+                .map(|(projects, _)| projects)
+                .map_err(|e| Error::new(ErrorKind::InvalidData, e))
+        }
+        Format::Ics(_) => Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Importing iCalendar files is not supported",
+        )),
+        Format::Taskwarrior => Ok(taskwarrior::import_projects(content, default_project_name)?),
+    }
+}
+
+/// Projects without a name in the imported file are named after the file.
+pub fn get_default_project_name(file: &Path) -> Result<String, Error> {
+    match file.file_stem().and_then(|s| s.to_str()) {
+        Some(stem) => Ok(String::from(stem)),
+        None => Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Cannot name a project after the imported file",
+        )),
+    }
+}
+
+/// Adds the tasks of the file to the projects. The message tells how many were imported and, for
+/// csv files, which field each column was read as.
+pub fn import_file(
+    storage: &dyn Storage,
+    file: &Path,
+    format: &Format,
+    column_mapping: Option<&str>,
+) -> Result<String, Error> {
+    let content = std::fs::read_to_string(file)?;
+    let default_project_name = get_default_project_name(file)?;
+    let (imported, mapping_description) = match format {
+        Format::Csv => csv::import_projects_with_mapping(
+            content.as_str(),
+            default_project_name,
+            column_mapping,
+        )
+        .map(|(projects, mapping)| (projects, Some(mapping)))
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?,
+        _ => (
+            import_projects(
+                content.as_str(),
+                format,
+                default_project_name,
+                column_mapping,
+            )?,
+            None,
+        ),
+    };
+    let project_count = imported.len();
+    let task_count = merge_into_storage(storage, imported)?;
+    let summary = format!(
+        "Imported {} task(s) into {} project(s)",
+        task_count, project_count
+    );
+    Ok(match mapping_description {
+        Some(mapping) => format!("Column mapping: {}\n{}", mapping, summary),
+        None => summary,
+    })
+}
+
+/// Formats that identify the tasks by their id.
+pub fn uses_task_ids(format: &Format) -> bool {
+    matches!(format, Format::Ics(_) | Format::Taskwarrior)
+}
+
+/// Tasks saved before they had an id get a new one every time they are loaded. Saving the
+/// exported projects keeps those ids, so exporting again gives the same identifiers.
+pub fn save_task_ids(
+    storage: &dyn Storage,
+    projects: &[Project],
+    format: &Format,
+) -> Result<(), Error> {
+    if uses_task_ids(format) {
+        for project in projects.iter() {
+            storage.save_project(project)?;
+        }
+    }
+    Ok(())
+}
+
+fn is_since(timestamp: Option<DateTime<Utc>>, since: Option<NaiveDate>) -> bool {
+    match since {
+        Some(since) => {
+            timestamp.map(|t| t.with_timezone(&Local).date_naive() >= since) == Some(true)
+        }
+        None => true,
+    }
+}
+
+/// Keeps the tasks updated on or after `updated_since` and completed on or after `completed_since`.
+/// Tasks without the timestamp a filter looks at are left out.
+pub fn filter_tasks(
+    projects: &[Project],
+    updated_since: Option<NaiveDate>,
+    completed_since: Option<NaiveDate>,
+) -> Vec<Project> {
+    let is_kept = |task: &Task| {
+        is_since(task.updated_at, updated_since) && is_since(task.completed_at, completed_since)
+    };
+    projects
+        .iter()
+        .map(|project| {
+            let mut filtered = project.clone();
+            filtered.active_tasks.retain(is_kept);
+            filtered.completed_tasks.retain(is_kept);
+            filtered
+        })
+        .collect()
+}
+
+pub fn export_file(projects: &[Project], file: &Path, format: &Format) -> Result<String, Error> {
+    std::fs::write(file, export_projects(projects, format))?;
+    Ok(format!(
+        "Exported {} project(s) to {}",
+        projects.len(),
+        file.to_str().unwrap()
+    ))
+}
+
+/// Adds the imported tasks to the stored projects, creating the projects that do not exist yet.
+/// Tasks whose name or id already exists in the project are skipped so importing twice is harmless.
+/// Returns the number of tasks added.
+pub fn merge_into_storage(storage: &dyn Storage, imported: Vec<Project>) -> Result<usize, Error> {
+    let mut added_tasks = 0;
+    for imported_project in imported {
+        let mut changes: Vec<ActivityEntry> = vec![];
+        let mut project = match storage.load_project(imported_project.name.clone()) {
+            Ok(project) => project,
+            Err(_) => {
+                let mut project = Project::new(imported_project.name.clone());
+                project.description = imported_project.description.clone();
+                project
+            }
+        };
+        let task_lists = [
+            (imported_project.active_tasks, false),
+            (imported_project.completed_tasks, true),
+        ];
+        for (tasks, completed) in task_lists.iter() {
+            for task in tasks.iter() {
+                let already_present = project
+                    .active_tasks
+                    .iter()
+                    .chain(project.completed_tasks.iter())
+                    .any(|t| t.name == task.name || t.id == task.id);
+                if already_present {
+                    continue;
+                }
+                match completed {
+                    true => project.completed_tasks.push(task.clone()),
+                    false => project.active_tasks.push(task.clone()),
+                }
+                changes.push(ActivityEntry::new(
+                    ActivityKind::AddTask,
+                    task.name.clone(),
+                    None,
+                    None,
+                ));
+            }
+        }
+        if !changes.is_empty() {
+            project.mark_updated();
+        }
+        storage.save_project(&project)?;
+        for change in changes.iter() {
+            storage.append_activity(project.name.clone(), change)?;
+        }
+        added_tasks += changes.len();
+    }
+    Ok(added_tasks)
+}
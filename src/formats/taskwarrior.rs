@@ -0,0 +1,259 @@
+use crate::structure::{Project, Task, TaskNote};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+static DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+#[derive(Serialize, Deserialize)]
+pub struct TaskwarriorAnnotation {
+    pub entry: String,
+    pub description: String,
+}
+
+/// A task of `task export`, only with the attributes that have a pman equivalent.
+#[derive(Serialize, Deserialize)]
+pub struct TaskwarriorTask {
+    pub uuid: Option<Uuid>,
+    pub description: String,
+    #[serde(default)]
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<TaskwarriorAnnotation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+}
+
+/// Taskwarrior only has H, M and L priorities.
+fn to_taskwarrior_priority(priority: char) -> String {
+    String::from(match priority {
+        'A' => "H",
+        'B' => "M",
+        _ => "L",
+    })
+}
+
+fn from_taskwarrior_priority(priority: &str) -> Option<char> {
+    match priority {
+        "H" => Some('A'),
+        "M" => Some('B'),
+        "L" => Some('C'),
+        _ => None,
+    }
+}
+
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.format(DATE_TIME_FORMAT).to_string()
+}
+
+fn parse_timestamp(value: Option<String>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|v| NaiveDateTime::parse_from_str(v.as_str(), DATE_TIME_FORMAT).ok())
+        .map(|t| Utc.from_utc_datetime(&t))
+}
+
+/// Taskwarrior stores a due date as the UTC time of the local midnight it starts at.
+fn format_due_date(due_date: NaiveDate) -> Option<String> {
+    due_date
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|midnight| format_timestamp(midnight.with_timezone(&Utc)))
+}
+
+fn parse_due_date(value: Option<String>) -> Option<NaiveDate> {
+    parse_timestamp(value).map(|due| due.with_timezone(&Local).date_naive())
+}
+
+fn to_taskwarrior_task(project_name: &str, task: &Task, completed: bool) -> TaskwarriorTask {
+    let described_at = format_timestamp(task.created_at.unwrap_or_else(Utc::now));
+    TaskwarriorTask {
+        uuid: Some(task.id),
+        description: task.name.clone(),
+        status: String::from(match completed {
+            true => "completed",
+            false => "pending",
+        }),
+        project: Some(String::from(project_name)),
+        tags: task.tags.clone(),
+        due: task.due_date.and_then(format_due_date),
+        priority: task.priority.map(to_taskwarrior_priority),
+        annotations: task
+            .description
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| TaskwarriorAnnotation {
+                entry: described_at.clone(),
+                description: String::from(l.trim()),
+            })
+            .chain(task.notes.iter().map(|note| TaskwarriorAnnotation {
+                entry: format_timestamp(note.at),
+                description: format!("{}: {}", note.author, note.text),
+            }))
+            .collect(),
+        estimate: match task.estimate {
+            0 => None,
+            estimate => Some(estimate),
+        },
+        entry: task.created_at.map(format_timestamp),
+        modified: task.updated_at.map(format_timestamp),
+        end: task.completed_at.map(format_timestamp),
+    }
+}
+
+/// Annotations exported from notes are read back as notes: `author: text`, the author being a
+/// user name, stamped later than the task was created as its description lines are.
+fn to_note(
+    annotation: &TaskwarriorAnnotation,
+    created_at: Option<DateTime<Utc>>,
+) -> Option<TaskNote> {
+    let at = parse_timestamp(Some(annotation.entry.clone()))?;
+    let (author, text) = annotation.description.split_once(": ")?;
+    if Some(at) == created_at || author.is_empty() || author.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(TaskNote {
+        at,
+        author: String::from(author),
+        text: String::from(text),
+    })
+}
+
+/// Returns the task and whether it is completed, `None` for deleted and recurring template tasks.
+fn from_taskwarrior_task(taskwarrior_task: TaskwarriorTask) -> Option<(Task, bool)> {
+    let completed = match taskwarrior_task.status.as_str() {
+        "completed" => true,
+        "deleted" | "recurring" => return None,
+        _ => false,
+    };
+    let created_at = parse_timestamp(taskwarrior_task.entry);
+    let mut description_lines: Vec<String> = vec![];
+    let mut notes: Vec<TaskNote> = vec![];
+    for annotation in taskwarrior_task.annotations.iter() {
+        match to_note(annotation, created_at) {
+            Some(note) => notes.push(note),
+            None => description_lines.push(annotation.description.clone()),
+        }
+    }
+    let mut task = Task::new(taskwarrior_task.description, description_lines.join("\n"));
+    task.notes = notes;
+    if let Some(uuid) = taskwarrior_task.uuid {
+        task.id = uuid;
+    }
+    task.tags = taskwarrior_task.tags;
+    task.due_date = parse_due_date(taskwarrior_task.due);
+    task.priority = taskwarrior_task
+        .priority
+        .and_then(|p| from_taskwarrior_priority(p.as_str()));
+    task.estimate = taskwarrior_task.estimate.unwrap_or(0);
+    task.created_at = created_at.or(task.created_at);
+    task.updated_at = parse_timestamp(taskwarrior_task.modified).or(task.updated_at);
+    task.completed_at = parse_timestamp(taskwarrior_task.end);
+    Some((task, completed))
+}
+
+pub fn export_projects(projects: &[Project]) -> String {
+    let mut taskwarrior_tasks: Vec<TaskwarriorTask> = vec![];
+    for project in projects.iter() {
+        for task in project.active_tasks.iter() {
+            taskwarrior_tasks.push(to_taskwarrior_task(project.name.as_str(), task, false));
+        }
+        for task in project.completed_tasks.iter() {
+            taskwarrior_tasks.push(to_taskwarrior_task(project.name.as_str(), task, true));
+        }
+    }
+    serde_json::to_string_pretty(&taskwarrior_tasks).unwrap()
+}
+
+/// Groups the tasks of a `task export` by their `project:`, tasks without one go to
+/// `default_project_name`.
+pub fn import_projects(
+    content: &str,
+    default_project_name: String,
+) -> Result<Vec<Project>, serde_json::Error> {
+    let taskwarrior_tasks: Vec<TaskwarriorTask> = serde_json::from_str(content)?;
+    let mut projects: Vec<Project> = vec![];
+    for taskwarrior_task in taskwarrior_tasks {
+        let project_name = taskwarrior_task
+            .project
+            .clone()
+            .unwrap_or_else(|| default_project_name.clone());
+        let (task, completed) = match from_taskwarrior_task(taskwarrior_task) {
+            Some(converted) => converted,
+            None => continue,
+        };
+        let project_index = match projects.iter().position(|p| p.name == project_name) {
+            Some(index) => index,
+            None => {
+                projects.push(Project::new(project_name));
+                projects.len() - 1
+            }
+        };
+        match completed {
+            true => projects[project_index].completed_tasks.push(task),
+            false => projects[project_index].active_tasks.push(task),
+        }
+    }
+    Ok(projects)
+}
+
+#[test]
+fn import_taskwarrior_export() {
+    let content = r#"[
+        {"id":1,"description":"Buy milk","entry":"20220101T120000Z","project":"Home",
+         "status":"pending","uuid":"5a4b2b6e-1b8e-4f4b-9a57-1d8a1e6f3c11","tags":["shop"],
+         "due":"20220105T230000Z","priority":"H","urgency":9.1,
+         "annotations":[{"entry":"20220102T080000Z","description":"Semi-skimmed"}]},
+        {"id":0,"description":"Old chore","status":"deleted","uuid":"7d3c4b1a-2f0e-4c5d-8b6a-9e8f7d6c5b4a"},
+        {"id":0,"description":"Fix sink","status":"completed","project":"Home","uuid":"0c2e7a31-7c9d-4e6b-9f5a-3b2d1c0e9f8a"}
+    ]"#;
+    let projects = import_projects(content, String::from("tasks")).unwrap();
+    assert_eq!(projects.len(), 1);
+    let task = &projects[0].active_tasks[0];
+    assert_eq!(task.id.to_string(), "5a4b2b6e-1b8e-4f4b-9a57-1d8a1e6f3c11");
+    assert_eq!(task.description, "Semi-skimmed");
+    assert_eq!(task.priority, Some('A'));
+    assert_eq!(
+        task.due_date,
+        parse_due_date(Some(String::from("20220105T230000Z")))
+    );
+    assert_eq!(
+        task.created_at.map(format_timestamp),
+        Some(String::from("20220101T120000Z"))
+    );
+    assert_eq!(projects[0].completed_tasks[0].name, "Fix sink");
+}
+
+#[test]
+fn export_round_trip() {
+    let mut project = Project::new(String::from("Home"));
+    let mut task = Task::new(String::from("Pay rent"), String::from("Landlord: Smith"));
+    task.due_date = NaiveDate::from_ymd_opt(2022, 3, 27);
+    task.created_at = parse_timestamp(Some(String::from("20220301T090000Z")));
+    task.notes
+        .push(TaskNote::new(String::from("Paid: by transfer")));
+    project.active_tasks.push(task);
+    let content = export_projects(&[project]);
+    let projects = import_projects(content.as_str(), String::from("tasks")).unwrap();
+    let task = &projects[0].active_tasks[0];
+    assert_eq!(task.due_date, NaiveDate::from_ymd_opt(2022, 3, 27));
+    assert_eq!(task.description, "Landlord: Smith");
+    assert_eq!(task.notes[0].text, "Paid: by transfer");
+    let exported: Vec<TaskwarriorTask> = serde_json::from_str(content.as_str()).unwrap();
+    assert_eq!(exported[0].annotations[0].entry, "20220301T090000Z");
+}
@@ -0,0 +1,245 @@
+use crate::structure::{Project, Task};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+
+static DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A single todo.txt line, with the `+project` it belongs to if any.
+pub struct TodoTxtEntry {
+    pub project: Option<String>,
+    pub task: Task,
+    pub completed: bool,
+}
+
+fn parse_date(token: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(token, DATE_FORMAT).ok()
+}
+
+/// todo.txt dates have no time, they are read as the start of the day in local time.
+fn to_timestamp(date: NaiveDate) -> Option<DateTime<Utc>> {
+    date.and_hms_opt(0, 0, 0)?
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp
+        .with_timezone(&Local)
+        .format(DATE_FORMAT)
+        .to_string()
+}
+
+fn parse_priority(token: &str) -> Option<char> {
+    let characters: Vec<char> = token.chars().collect();
+    match characters.as_slice() {
+        ['(', priority, ')'] if priority.is_ascii_uppercase() => Some(*priority),
+        _ => None,
+    }
+}
+
+/// todo.txt words cannot contain spaces, they are replaced by underscores, and underscores and
+/// backslashes of the value are escaped with a backslash so `from_word` gets the value back.
+fn to_word(value: &str) -> String {
+    let mut word = String::new();
+    for character in value.trim().chars() {
+        match character {
+            ' ' => word.push('_'),
+            '_' | '\\' => {
+                word.push('\\');
+                word.push(character);
+            }
+            _ => word.push(character),
+        }
+    }
+    word
+}
+
+fn from_word(word: &str) -> String {
+    let mut value = String::new();
+    let mut characters = word.chars();
+    while let Some(character) = characters.next() {
+        match character {
+            '_' => value.push(' '),
+            '\\' => value.push(characters.next().unwrap_or('\\')),
+            _ => value.push(character),
+        }
+    }
+    value
+}
+
+pub fn parse_line(line: &str) -> Option<TodoTxtEntry> {
+    let mut tokens = line.split_whitespace().peekable();
+    tokens.peek()?;
+    let mut entry = TodoTxtEntry {
+        project: None,
+        task: Task::new(String::new(), String::new()),
+        completed: false,
+    };
+    if tokens.peek() == Some(&"x") {
+        entry.completed = true;
+        tokens.next();
+        if let Some(completion_date) = tokens.peek().and_then(|t| parse_date(t)) {
+            entry.task.completed_at = to_timestamp(completion_date);
+            tokens.next();
+        }
+    }
+    if let Some(priority) = tokens.peek().and_then(|t| parse_priority(t)) {
+        entry.task.priority = Some(priority);
+        tokens.next();
+    }
+    if let Some(creation_date) = tokens.peek().and_then(|t| parse_date(t)) {
+        entry.task.created_at = to_timestamp(creation_date).or(entry.task.created_at);
+        tokens.next();
+    }
+
+    let mut words: Vec<&str> = vec![];
+    for token in tokens {
+        if token.len() > 1 && token.starts_with('+') && entry.project.is_none() {
+            entry.project = Some(from_word(&token[1..]));
+        } else if token.len() > 1 && token.starts_with('@') {
+            entry.task.tags.push(from_word(&token[1..]));
+        } else if let Some(date) = token.strip_prefix("due:").and_then(parse_date) {
+            entry.task.due_date = Some(date);
+        } else if let Some(date) = token.strip_prefix("created:").and_then(parse_date) {
+            entry.task.created_at = to_timestamp(date).or(entry.task.created_at);
+        } else if let Some(priority) = token
+            .strip_prefix("pri:")
+            .and_then(|p| parse_priority(format!("({})", p).as_str()))
+        {
+            entry.task.priority = Some(priority);
+        } else if let Some(estimate) = token.strip_prefix("estimate:").and_then(|v| v.parse().ok())
+        {
+            entry.task.estimate = estimate;
+        } else if let Some(time_spent) = token.strip_prefix("spent:").and_then(|v| v.parse().ok()) {
+            entry.task.time_spent = time_spent;
+        } else {
+            words.push(token);
+        }
+    }
+    entry.task.name = words.join(" ");
+    Some(entry)
+}
+
+/// The description and the notes of the task are left out, todo.txt having one line per task.
+pub fn task_to_line(project_name: &str, task: &Task, completed: bool) -> String {
+    let mut line = String::new();
+    if completed {
+        line.push_str("x ");
+        // The creation date of a completed task can only follow its completion date.
+        if let (Some(completed_at), Some(created_at)) = (task.completed_at, task.created_at) {
+            line.push_str(format!("{} ", format_timestamp(completed_at)).as_str());
+            line.push_str(format!("{} ", format_timestamp(created_at)).as_str());
+        } else if let Some(completed_at) = task.completed_at {
+            line.push_str(format!("{} ", format_timestamp(completed_at)).as_str());
+        }
+    } else {
+        if let Some(priority) = task.priority {
+            line.push_str(format!("({}) ", priority).as_str());
+        }
+        if let Some(created_at) = task.created_at {
+            line.push_str(format!("{} ", format_timestamp(created_at)).as_str());
+        }
+    }
+    line.push_str(task.name.trim());
+    if !project_name.is_empty() {
+        line.push_str(format!(" +{}", to_word(project_name)).as_str());
+    }
+    for tag in task.tags.iter() {
+        line.push_str(format!(" @{}", to_word(tag)).as_str());
+    }
+    if let Some(due_date) = task.due_date {
+        line.push_str(format!(" due:{}", due_date.format(DATE_FORMAT)).as_str());
+    }
+    if completed {
+        if let Some(priority) = task.priority {
+            line.push_str(format!(" pri:{}", priority).as_str());
+        }
+        if let (None, Some(created_at)) = (task.completed_at, task.created_at) {
+            line.push_str(format!(" created:{}", format_timestamp(created_at)).as_str());
+        }
+    }
+    if task.estimate != 0 {
+        line.push_str(format!(" estimate:{}", task.estimate).as_str());
+    }
+    if task.time_spent != 0 {
+        line.push_str(format!(" spent:{}", task.time_spent).as_str());
+    }
+    line
+}
+
+pub fn export_projects(projects: &[Project]) -> String {
+    let mut lines: Vec<String> = vec![];
+    for project in projects.iter() {
+        for task in project.active_tasks.iter() {
+            lines.push(task_to_line(project.name.as_str(), task, false));
+        }
+        for task in project.completed_tasks.iter() {
+            lines.push(task_to_line(project.name.as_str(), task, true));
+        }
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// Groups the lines by their first `+project`, lines without one go to `default_project_name`.
+pub fn import_projects(content: &str, default_project_name: String) -> Vec<Project> {
+    let mut projects: Vec<Project> = vec![];
+    for entry in content.lines().filter_map(parse_line) {
+        let project_name = entry
+            .project
+            .unwrap_or_else(|| default_project_name.clone());
+        let project_index = match projects.iter().position(|p| p.name == project_name) {
+            Some(index) => index,
+            None => {
+                projects.push(Project::new(project_name));
+                projects.len() - 1
+            }
+        };
+        match entry.completed {
+            true => projects[project_index].completed_tasks.push(entry.task),
+            false => projects[project_index].active_tasks.push(entry.task),
+        }
+    }
+    projects
+}
+
+#[test]
+fn parse_todotxt_line() {
+    let entry =
+        parse_line("(A) 2021-01-02 Call mom +Family @phone due:2021-01-05 estimate:2").unwrap();
+    assert_eq!(entry.project, Some(String::from("Family")));
+    assert_eq!(entry.task.name, "Call mom");
+    assert_eq!(entry.task.priority, Some('A'));
+    assert_eq!(entry.task.tags, vec![String::from("phone")]);
+    assert_eq!(entry.task.due_date, NaiveDate::from_ymd_opt(2021, 1, 5));
+    assert_eq!(entry.task.estimate, 2);
+    assert_eq!(
+        entry.task.created_at.map(format_timestamp),
+        Some(String::from("2021-01-02"))
+    );
+    assert!(!entry.completed);
+}
+
+#[test]
+fn todotxt_round_trip() {
+    let lines = [
+        "(B) 2022-03-01 Write report +Work @office due:2022-03-04",
+        "x 2022-03-05 2022-03-01 Ship release +Work @ci pri:A spent:3",
+        "2022-03-01 Plan the trip +My_Project @on\\_call",
+        "x Book the hotel +My_Project created:2022-03-01",
+    ];
+    for line in lines.iter() {
+        let entry = parse_line(line).unwrap();
+        let project = entry.project.unwrap();
+        assert_eq!(
+            task_to_line(project.as_str(), &entry.task, entry.completed),
+            *line
+        );
+    }
+    let mut project = Project::new(String::from("My Project"));
+    project
+        .active_tasks
+        .push(Task::new(String::from("Plan"), String::new()));
+    let imported = import_projects(export_projects(&[project]).as_str(), String::new());
+    assert_eq!(imported[0].name, "My Project");
+}
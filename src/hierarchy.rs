@@ -0,0 +1,210 @@
+use crate::structure::Project;
+use std::collections::{BTreeMap, HashSet};
+
+/// A line of the project tree: a project, or a group only known as the parent of projects.
+#[derive(Clone, Default)]
+pub struct ProjectTreeRow {
+    pub name: String,
+    pub depth: usize,
+    pub project: Option<Project>,
+    pub has_children: bool,
+    pub collapsed: bool,
+    /// Completed and total tasks of the project and of all its sub-projects.
+    pub completed_tasks: usize,
+    pub total_tasks: usize,
+}
+
+/// Projects organised by their parent, or their folder group when they have none, groups being
+/// the parents that are not projects.
+pub struct ProjectHierarchy {
+    projects: Vec<Project>,
+    /// Parent of every project and group, by name. Parents that would make a project its own
+    /// ancestor are ignored.
+    parents: BTreeMap<String, Option<String>>,
+}
+
+impl ProjectHierarchy {
+    pub fn from_projects(projects: &[Project]) -> ProjectHierarchy {
+        let mut parents: BTreeMap<String, Option<String>> = projects
+            .iter()
+            .map(|p| {
+                (
+                    p.name.clone(),
+                    p.parent.clone().or_else(|| p.folder_group.clone()),
+                )
+            })
+            .collect();
+        let names: Vec<String> = parents.keys().cloned().collect();
+        for name in names {
+            if Self::leads_back_to(&parents, &name) {
+                parents.insert(name, None);
+            }
+        }
+        let groups: Vec<String> = parents
+            .values()
+            .flatten()
+            .filter(|parent| !parents.contains_key(*parent))
+            .cloned()
+            .collect();
+        for group in groups {
+            parents.insert(group, None);
+        }
+        ProjectHierarchy {
+            projects: projects.to_vec(),
+            parents,
+        }
+    }
+
+    fn leads_back_to(parents: &BTreeMap<String, Option<String>>, name: &str) -> bool {
+        let mut visited: HashSet<String> = HashSet::new();
+        let mut current = parents.get(name).cloned().flatten();
+        while let Some(parent) = current {
+            if parent == name {
+                return true;
+            }
+            if !visited.insert(parent.clone()) {
+                return false;
+            }
+            current = parents.get(&parent).cloned().flatten();
+        }
+        false
+    }
+
+    pub fn get_project(&self, name: &str) -> Option<&Project> {
+        self.projects.iter().find(|p| p.name == name)
+    }
+
+    /// The projects and groups directly under `name`, or the top level ones for `None`.
+    pub fn get_children(&self, name: Option<&str>) -> Vec<String> {
+        self.parents
+            .iter()
+            .filter(|(_, parent)| parent.as_deref() == name)
+            .map(|(child, _)| child.clone())
+            .collect()
+    }
+
+    /// Whether `name` is `ancestor` or one of its sub-projects, at any depth.
+    pub fn is_within(&self, name: &str, ancestor: &str) -> bool {
+        let mut current = Some(name.to_string());
+        while let Some(node) = current {
+            if node == ancestor {
+                return true;
+            }
+            current = self.parents.get(&node).cloned().flatten();
+        }
+        false
+    }
+
+    /// Completed and total tasks of `name` and of everything under it.
+    pub fn get_task_counts(&self, name: &str) -> (usize, usize) {
+        let (mut completed, mut total) = match self.get_project(name) {
+            Some(project) => (
+                project.completed_tasks.len(),
+                project.active_tasks.len() + project.completed_tasks.len(),
+            ),
+            None => (0, 0),
+        };
+        for child in self.get_children(Some(name)) {
+            let (child_completed, child_total) = self.get_task_counts(child.as_str());
+            completed += child_completed;
+            total += child_total;
+        }
+        (completed, total)
+    }
+
+    fn add_rows(
+        &self,
+        name: String,
+        depth: usize,
+        collapsed: &HashSet<String>,
+        rows: &mut Vec<ProjectTreeRow>,
+    ) {
+        let children = self.get_children(Some(name.as_str()));
+        let (completed_tasks, total_tasks) = self.get_task_counts(name.as_str());
+        let is_collapsed = collapsed.contains(&name);
+        rows.push(ProjectTreeRow {
+            project: self.get_project(name.as_str()).cloned(),
+            name,
+            depth,
+            has_children: !children.is_empty(),
+            collapsed: is_collapsed,
+            completed_tasks,
+            total_tasks,
+        });
+        if !is_collapsed {
+            for child in children {
+                self.add_rows(child, depth + 1, collapsed, rows);
+            }
+        }
+    }
+
+    /// The lines of the tree, sub-projects following their parent unless it is collapsed.
+    pub fn get_rows(&self, collapsed: &HashSet<String>) -> Vec<ProjectTreeRow> {
+        let mut rows = vec![];
+        for root in self.get_children(None) {
+            self.add_rows(root, 0, collapsed, &mut rows);
+        }
+        rows
+    }
+}
+
+impl ProjectTreeRow {
+    pub fn describe(&self) -> String {
+        let marker = match (self.has_children, self.collapsed) {
+            (false, _) => " ",
+            (true, false) => "v",
+            (true, true) => ">",
+        };
+        let kind = match self.project {
+            Some(_) => "",
+            None => " (group)",
+        };
+        format!(
+            "{}{} {}{} [{}/{}]",
+            "  ".repeat(self.depth),
+            marker,
+            self.name,
+            kind,
+            self.completed_tasks,
+            self.total_tasks
+        )
+    }
+}
+
+#[test]
+fn completion_rolls_up_to_parents() {
+    use crate::structure::Task;
+    let mut website = Project::new(String::from("Website"));
+    website.parent = Some(String::from("Work"));
+    website.active_tasks = vec![Task::new(String::from("Deploy"), String::new())];
+    let mut blog = Project::new(String::from("Blog"));
+    blog.parent = Some(String::from("Website"));
+    blog.completed_tasks = vec![Task::new(String::from("Post"), String::new())];
+    let mut looping = Project::new(String::from("Loop"));
+    looping.parent = Some(String::from("Loop"));
+    let mut notes = Project::new(String::from("Notes"));
+    notes.folder_group = Some(String::from("Work"));
+    let hierarchy = ProjectHierarchy::from_projects(&[website, blog, looping, notes]);
+
+    assert_eq!(hierarchy.get_children(None), vec!["Loop", "Work"]);
+    assert_eq!(
+        hierarchy.get_children(Some("Work")),
+        vec!["Notes", "Website"]
+    );
+    assert_eq!(hierarchy.get_task_counts("Work"), (1, 2));
+    assert!(hierarchy.is_within("Blog", "Work"));
+    let rows = hierarchy.get_rows(&HashSet::new());
+    let names: Vec<(&str, usize)> = rows.iter().map(|r| (r.name.as_str(), r.depth)).collect();
+    assert_eq!(
+        names,
+        vec![
+            ("Loop", 0),
+            ("Work", 0),
+            ("Notes", 1),
+            ("Website", 1),
+            ("Blog", 2)
+        ]
+    );
+    let collapsed: HashSet<String> = vec![String::from("Website")].into_iter().collect();
+    assert_eq!(hierarchy.get_rows(&collapsed).len(), 4);
+}
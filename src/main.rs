@@ -1,54 +1,167 @@
 mod structure;
-use crossterm::event::{self, Event as CEvent};
-use std::{
-    sync::mpsc,
-    thread,
-    time::{Duration, Instant},
+use crate::cli::Command;
+use crossterm::event::{
+    self, Event as CEvent, KeyCode, KeyEventKind, KeyModifiers, MouseEventKind,
 };
+use std::{sync::mpsc, thread};
 
 #[macro_use]
 extern crate smart_default;
 
+mod activity;
+mod cli;
+mod commands;
+mod dependencies;
+mod formats;
+mod hierarchy;
+mod recurrence;
 mod services;
+mod storage;
+mod templates;
+mod terminal;
+mod theme;
 mod ui;
 mod utils;
 
 enum Event<I> {
     Input(I),
-    Tick,
+    Mouse(event::MouseEvent),
+    /// Text pasted in the terminal, received at once rather than as keys.
+    Paste(String),
+    /// The terminal changed size, the layout has to be computed again.
+    Resize,
+    /// The terminal gained (true) or lost (false) the focus.
+    Focus(bool),
+    /// The process was asked to stop by a signal.
+    Terminate,
 }
 
 fn main() {
-    utils::create_working_folder_if_not_exist();
-    let (tx, rx) = mpsc::channel();
-    let tick_rate = Duration::from_millis(250);
-    thread::spawn(move || {
-        let mut last_tick = Instant::now();
-        loop {
-            // poll for tick rate duration, if no events, sent tick event.
-            let timeout = tick_rate
-                .checked_sub(last_tick.elapsed())
-                .unwrap_or_else(|| Duration::from_secs(0));
-            if event::poll(timeout).unwrap() {
-                if let CEvent::Key(key) = event::read().unwrap() {
-                    tx.send(Event::Input(key)).unwrap();
-                }
-            }
-            if last_tick.elapsed() >= tick_rate {
-                tx.send(Event::Tick).unwrap();
-                last_tick = Instant::now();
+    let arguments = match cli::parse_arguments(std::env::args().skip(1).collect()) {
+        Ok(arguments) => arguments,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("{}", message);
             }
+            eprintln!("{}", cli::USAGE);
+            std::process::exit(1);
+        }
+    };
+    let working_folder = utils::get_working_folder(arguments.base_path);
+    utils::create_working_folder_if_not_exist(&working_folder);
+    let result = match arguments.command {
+        Command::Interactive => {
+            run_interactive(working_folder);
+            return;
+        }
+        Command::Migrate { to, force } => storage::migrate(working_folder, to, force),
+        Command::Import {
+            file,
+            format,
+            column_mapping,
+        } => commands::import(working_folder, file, format, column_mapping),
+        Command::Export {
+            format,
+            output,
+            project,
+            updated_since,
+            completed_since,
+        } => commands::export(
+            working_folder,
+            format,
+            output,
+            project,
+            updated_since,
+            completed_since,
+        ),
+        Command::Sync { file, watch } => commands::sync(working_folder, file, watch),
+        Command::Log { project } => commands::log(working_folder, project),
+        Command::Search {
+            query,
+            include_archived,
+        } => commands::search(working_folder, query, include_archived),
+    };
+    match result {
+        Ok(message) => println!("{}", message),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_interactive(working_folder: std::path::PathBuf) {
+    // Read before the interface takes the terminal, so a broken configuration can be reported.
+    let theme = match theme::load_theme() {
+        Ok(theme) => theme,
+        Err(message) => {
+            eprintln!("Error: {}", message);
+            std::process::exit(1);
+        }
+    };
+    let (tx, rx) = mpsc::channel();
+    let signal_tx = tx.clone();
+    if let Err(e) = terminal::handle_termination_signals(move || {
+        let _ = signal_tx.send(Event::Terminate);
+    }) {
+        eprintln!("Error when handling termination signals: {}", e);
+    }
+    thread::spawn(move || loop {
+        let event = match event::read().unwrap() {
+            // Some terminals also report releases, a key acts when pressed.
+            CEvent::Key(key) if key.kind == KeyEventKind::Release => continue,
+            CEvent::Key(key) => Event::Input(key),
+            CEvent::Mouse(mouse_event) => Event::Mouse(mouse_event),
+            CEvent::Paste(text) => Event::Paste(text),
+            CEvent::Resize(_, _) => Event::Resize,
+            CEvent::FocusGained => Event::Focus(true),
+            CEvent::FocusLost => Event::Focus(false),
+        };
+        if tx.send(event).is_err() {
+            break;
         }
     });
 
-    let mut app = structure::Application::new(utils::get_working_folder());
+    terminal::install_panic_hook();
+    // Every change is saved as it is made, stopping only has to leave the terminal clean,
+    // which dropping the application does.
+    let mut app = structure::Application::new(working_folder, theme);
+    // Drawing only follows what can change the screen, nothing is drawn while the terminal idles.
+    let mut needs_redraw = true;
     while app.is_running {
-        app.update();
-        match rx.recv().unwrap() {
-            Event::Input(event) => match event.code {
-                _ => app.handle_inputs(event.code),
-            },
-            Event::Tick => {}
+        if needs_redraw {
+            app.update();
         }
+        needs_redraw = match rx.recv().unwrap() {
+            Event::Input(event) => {
+                match event.code {
+                    // Raw mode reads Ctrl-C as a key rather than as an interruption
+                    KeyCode::Char('c') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.quit()
+                    }
+                    KeyCode::Char('p') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.show_command_palette()
+                    }
+                    _ => app.handle_inputs(event.code),
+                }
+                true
+            }
+            Event::Terminate => {
+                app.quit();
+                false
+            }
+            Event::Mouse(mouse_event) => {
+                app.handle_mouse(mouse_event);
+                mouse_event.kind != MouseEventKind::Moved
+            }
+            Event::Paste(text) => {
+                app.handle_paste(&text);
+                true
+            }
+            // Drawing resizes the interface to the terminal and clears what the resize left.
+            Event::Resize => true,
+            // Other programs may have written over the screen while the terminal was unfocused.
+            Event::Focus(gained) => gained,
+        };
     }
 }
@@ -0,0 +1,168 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// How a task repeats once it is completed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Recurrence {
+    Daily,
+    /// On the given weekdays, or on the weekday of the due date when none are given.
+    Weekly(Vec<Weekday>),
+    /// On this day of every month, the last day of the month for shorter months.
+    Monthly(u32),
+    DaysAfterCompletion(u32),
+}
+
+pub static RULE_EXAMPLES: &str = "daily, weekly mon,thu, monthly 15, every 3 days or none";
+
+fn get_days_in_month(year: i32, month: u32) -> u32 {
+    let first_of_next_month = match month {
+        12 => NaiveDate::from_ymd_opt(year + 1, 1, 1),
+        _ => NaiveDate::from_ymd_opt(year, month + 1, 1),
+    };
+    first_of_next_month.unwrap().pred_opt().unwrap().day()
+}
+
+fn get_day_of_month(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day.min(get_days_in_month(year, month))).unwrap()
+}
+
+impl Recurrence {
+    /// Reads a rule as written by `to_rule`, `none` giving no recurrence.
+    pub fn parse(rule: &str) -> Result<Option<Recurrence>, String> {
+        let words: Vec<String> = rule.split_whitespace().map(|w| w.to_lowercase()).collect();
+        let words: Vec<&str> = words.iter().map(|w| w.as_str()).collect();
+        match words.as_slice() {
+            [] | ["none"] => Ok(None),
+            ["daily"] => Ok(Some(Recurrence::Daily)),
+            ["weekly"] => Ok(Some(Recurrence::Weekly(vec![]))),
+            // The days may be separated by spaces as well as commas: `weekly mon, thu`.
+            ["weekly", days @ ..] => {
+                let days = days.join(",");
+                let weekdays: Result<Vec<Weekday>, String> = days
+                    .split(',')
+                    .map(|d| d.trim())
+                    .filter(|d| !d.is_empty())
+                    .map(|d| {
+                        d.parse::<Weekday>()
+                            .map_err(|_| format!("Unknown weekday: {}", d))
+                    })
+                    .collect();
+                Ok(Some(Recurrence::Weekly(weekdays?)))
+            }
+            ["monthly", day] => match day.parse::<u32>() {
+                Ok(day) if (1..=31).contains(&day) => Ok(Some(Recurrence::Monthly(day))),
+                _ => Err(format!("Invalid day of the month: {}", day)),
+            },
+            ["every", days, "day"] | ["every", days, "days"] => match days.parse::<u32>() {
+                Ok(days) if days > 0 => Ok(Some(Recurrence::DaysAfterCompletion(days))),
+                _ => Err(format!("Invalid number of days: {}", days)),
+            },
+            _ => Err(format!(
+                "Unknown repetition \"{}\", use {}",
+                rule, RULE_EXAMPLES
+            )),
+        }
+    }
+
+    pub fn to_rule(&self) -> String {
+        match self {
+            Recurrence::Daily => String::from("daily"),
+            Recurrence::Weekly(days) if days.is_empty() => String::from("weekly"),
+            Recurrence::Weekly(days) => format!(
+                "weekly {}",
+                days.iter()
+                    .map(|d| d.to_string().to_lowercase())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            Recurrence::Monthly(day) => format!("monthly {}", day),
+            Recurrence::DaysAfterCompletion(days) => format!("every {} days", days),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Recurrence::Daily => String::from("Repeats daily"),
+            Recurrence::Weekly(days) if days.is_empty() => String::from("Repeats weekly"),
+            Recurrence::Weekly(days) => format!(
+                "Repeats weekly on {}",
+                days.iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Recurrence::Monthly(day) => format!("Repeats monthly on day {}", day),
+            Recurrence::DaysAfterCompletion(days) => {
+                format!("Repeats {} days after completion", days)
+            }
+        }
+    }
+
+    /// Due date of the instance that follows one due on `due_date` and completed on
+    /// `completion_day`. Scheduled rules pick their first day after both, so an instance
+    /// completed late does not create one that is already overdue.
+    pub fn get_next_due_date(
+        &self,
+        due_date: Option<NaiveDate>,
+        completion_day: NaiveDate,
+    ) -> NaiveDate {
+        let reference = due_date.map_or(completion_day, |d| d.max(completion_day));
+        match self {
+            Recurrence::Daily => reference + Duration::days(1),
+            Recurrence::Weekly(days) => {
+                let days = match days.is_empty() {
+                    true => vec![due_date.unwrap_or(completion_day).weekday()],
+                    false => days.clone(),
+                };
+                (1..=7)
+                    .map(|offset| reference + Duration::days(offset))
+                    .find(|day| days.contains(&day.weekday()))
+                    .unwrap()
+            }
+            Recurrence::Monthly(day) => {
+                let this_month = get_day_of_month(reference.year(), reference.month(), *day);
+                match (this_month > reference, reference.month()) {
+                    (true, _) => this_month,
+                    (false, 12) => get_day_of_month(reference.year() + 1, 1, *day),
+                    (false, month) => get_day_of_month(reference.year(), month + 1, *day),
+                }
+            }
+            Recurrence::DaysAfterCompletion(days) => completion_day + Duration::days(*days as i64),
+        }
+    }
+}
+
+#[test]
+fn next_due_dates() {
+    let date = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+    let weekly = Recurrence::parse("weekly mon,thu").unwrap().unwrap();
+    // Due on a Monday, completed on time.
+    assert_eq!(
+        weekly.get_next_due_date(Some(date("2022-10-03")), date("2022-10-03")),
+        date("2022-10-06")
+    );
+    // Completed late, the next instance is not already overdue.
+    assert_eq!(
+        weekly.get_next_due_date(Some(date("2022-10-03")), date("2022-10-11")),
+        date("2022-10-13")
+    );
+    let monthly = Recurrence::parse("monthly 31").unwrap().unwrap();
+    assert_eq!(
+        monthly.get_next_due_date(Some(date("2022-01-31")), date("2022-01-30")),
+        date("2022-02-28")
+    );
+    let after_completion = Recurrence::parse("every 3 days").unwrap().unwrap();
+    assert_eq!(
+        after_completion.get_next_due_date(Some(date("2022-10-01")), date("2022-10-05")),
+        date("2022-10-08")
+    );
+    assert_eq!(
+        Recurrence::parse("weekly Mon, thu"),
+        Ok(Some(weekly.clone()))
+    );
+    assert_eq!(
+        Recurrence::parse(weekly.to_rule().as_str()),
+        Ok(Some(weekly))
+    );
+    assert!(Recurrence::parse("fortnightly").is_err());
+}
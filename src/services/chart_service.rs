@@ -0,0 +1,282 @@
+use crate::structure::{Project, Task};
+use crate::theme::Theme;
+use crate::ui::{
+    describe_controls, find_action, Completable, Drawable, HelpSection, InputMode, InputReceptor,
+    KeyBinding,
+};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
+use crossterm::event::KeyCode;
+use std::io::Stdout;
+use tui::backend::CrosstermBackend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::symbols::Marker;
+use tui::text::Span;
+use tui::widgets::{Axis, BarChart, Block, Borders, Chart, Clear, Dataset, GraphType};
+use tui::Frame;
+
+static VELOCITY_WEEKS: i64 = 8;
+
+pub struct Burndown {
+    pub first_day: NaiveDate,
+    /// Remaining work at the end of each day since `first_day`.
+    pub remaining: Vec<f64>,
+    /// The remaining work is counted in tasks when no task has an estimate.
+    pub uses_estimate: bool,
+}
+
+fn to_local_day(time: DateTime<Utc>) -> NaiveDate {
+    time.with_timezone(&Local).date_naive()
+}
+
+fn is_remaining_on(task: &Task, completed: bool, day: NaiveDate) -> bool {
+    let created = task.created_at.map(|c| to_local_day(c) <= day) != Some(false);
+    // Completed tasks without a completion time were completed before any tracked day
+    let not_yet_completed = match completed {
+        true => task.completed_at.map(|c| to_local_day(c) > day) == Some(true),
+        false => true,
+    };
+    created && not_yet_completed
+}
+
+pub fn get_burndown(project: &Project, today: NaiveDate) -> Burndown {
+    let all_tasks: Vec<(&Task, bool)> = project
+        .active_tasks
+        .iter()
+        .map(|t| (t, false))
+        .chain(project.completed_tasks.iter().map(|t| (t, true)))
+        .collect();
+    let uses_estimate = all_tasks.iter().any(|(t, _)| t.estimate > 0);
+    let first_day = all_tasks
+        .iter()
+        .filter_map(|(t, _)| t.created_at.map(to_local_day))
+        .min()
+        .unwrap_or(today)
+        .min(today);
+    let remaining = (0..=(today - first_day).num_days())
+        .map(|day_index| {
+            let day = first_day + Duration::days(day_index);
+            all_tasks
+                .iter()
+                .filter(|(task, completed)| is_remaining_on(task, *completed, day))
+                .map(|(task, _)| match uses_estimate {
+                    true => task.estimate as f64,
+                    false => 1.0,
+                })
+                .sum()
+        })
+        .collect();
+    Burndown {
+        first_day,
+        remaining,
+        uses_estimate,
+    }
+}
+
+/// Counts the tasks completed in each of the last `week_count` weeks, starting on mondays,
+/// oldest first.
+pub fn get_velocity(project: &Project, today: NaiveDate, week_count: i64) -> Vec<(NaiveDate, u64)> {
+    let current_week = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let mut weeks: Vec<(NaiveDate, u64)> = (0..week_count)
+        .rev()
+        .map(|weeks_ago| (current_week - Duration::weeks(weeks_ago), 0))
+        .collect();
+    for completed_at in project
+        .completed_tasks
+        .iter()
+        .filter_map(|t| t.completed_at)
+    {
+        let completion_day = to_local_day(completed_at);
+        if let Some(week) = weeks.iter_mut().find(|(start, _)| {
+            *start <= completion_day && completion_day < *start + Duration::weeks(1)
+        }) {
+            week.1 += 1;
+        }
+    }
+    weeks
+}
+
+#[derive(Default)]
+pub struct ProjectChartService {
+    project: Project,
+    is_active: bool,
+    is_done: bool,
+}
+
+impl ProjectChartService {
+    pub fn new(project: Project) -> ProjectChartService {
+        ProjectChartService {
+            project,
+            is_active: true,
+            is_done: false,
+        }
+    }
+
+    fn display_burndown(
+        &self,
+        frame: &mut Frame<CrosstermBackend<Stdout>>,
+        layout: Rect,
+        theme: &Theme,
+    ) {
+        let today = Local::now().date_naive();
+        let burndown = get_burndown(&self.project, today);
+        let points: Vec<(f64, f64)> = burndown
+            .remaining
+            .iter()
+            .enumerate()
+            .map(|(day, remaining)| (day as f64, *remaining))
+            .collect();
+        let last_day = std::cmp::max(points.len() as i64 - 1, 1) as f64;
+        let highest = burndown.remaining.iter().cloned().fold(1.0, f64::max);
+        let ideal_points = vec![(0.0, burndown.remaining[0]), (last_day, 0.0)];
+        let datasets = vec![
+            Dataset::default()
+                .name("Ideal")
+                .marker(Marker::Dot)
+                .graph_type(GraphType::Line)
+                .style(theme.muted_style())
+                .data(&ideal_points),
+            Dataset::default()
+                .name("Remaining")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(theme.accent_style())
+                .data(&points),
+        ];
+        let title = match burndown.uses_estimate {
+            true => "Burndown (remaining estimate)",
+            false => "Burndown (remaining tasks, no estimates set)",
+        };
+        let chart = Chart::new(datasets)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .x_axis(Axis::default().bounds([0.0, last_day]).labels(vec![
+                Span::raw(burndown.first_day.format("%Y-%m-%d").to_string()),
+                Span::raw(today.format("%Y-%m-%d").to_string()),
+            ]))
+            .y_axis(
+                Axis::default()
+                    .bounds([0.0, highest])
+                    .labels(vec![Span::raw("0"), Span::raw(format!("{}", highest))]),
+            );
+        frame.render_widget(chart, layout);
+    }
+
+    fn display_velocity(
+        &self,
+        frame: &mut Frame<CrosstermBackend<Stdout>>,
+        layout: Rect,
+        theme: &Theme,
+    ) {
+        let velocity = get_velocity(&self.project, Local::now().date_naive(), VELOCITY_WEEKS);
+        let labels: Vec<String> = velocity
+            .iter()
+            .map(|(week_start, _)| week_start.format("%m-%d").to_string())
+            .collect();
+        let bar_data: Vec<(&str, u64)> = labels
+            .iter()
+            .zip(velocity.iter())
+            .map(|(label, (_, count))| (label.as_str(), *count))
+            .collect();
+        let velocity_chart = BarChart::default()
+            .block(
+                Block::default()
+                    .title("Velocity (tasks completed per week)")
+                    .borders(Borders::ALL),
+            )
+            .data(&bar_data)
+            .bar_width(5)
+            .bar_style(theme.accent_style())
+            .value_style(theme.accent_value_style());
+        frame.render_widget(velocity_chart, layout);
+    }
+}
+
+impl Drawable for ProjectChartService {
+    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect, theme: &Theme) {
+        let popup_layout = self.centered_rect(90, 90, layout);
+        frame.render_widget(Clear, popup_layout);
+        let charts_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .split(popup_layout);
+        self.display_burndown(frame, charts_layout[0], theme);
+        self.display_velocity(frame, charts_layout[1], theme);
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ChartAction {
+    Close,
+}
+
+static CHART_BINDINGS: &[KeyBinding<ChartAction>] = &[KeyBinding::new(
+    &[KeyCode::Esc, KeyCode::Enter, KeyCode::Char('b')],
+    "Esc, B",
+    "Close charts",
+    ChartAction::Close,
+)];
+
+impl InputReceptor for ProjectChartService {
+    fn handle_input_key(&mut self, key_code: KeyCode) {
+        match find_action(CHART_BINDINGS, key_code) {
+            Some(ChartAction::Close) => self.is_done = true,
+            None => {}
+        }
+    }
+
+    fn get_controls_description(&self) -> String {
+        describe_controls(CHART_BINDINGS)
+    }
+
+    fn get_help_sections(&self) -> Vec<HelpSection> {
+        vec![HelpSection::from_bindings("Charts", CHART_BINDINGS)]
+    }
+
+    fn get_input_mode(&self) -> InputMode {
+        InputMode::CommandMode
+    }
+}
+
+impl Completable for ProjectChartService {
+    fn is_completed(&self) -> bool {
+        self.is_done
+    }
+
+    fn reset_completion(&mut self) {
+        self.is_done = false;
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    fn set_active(&mut self, new_active: bool) {
+        self.is_active = new_active;
+    }
+}
+
+#[test]
+fn burndown_follows_completions() {
+    let today = NaiveDate::from_ymd_opt(2022, 6, 3).unwrap();
+    let at_noon = |day: NaiveDate| {
+        day.and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap()
+            .with_timezone(&Utc)
+    };
+    let mut project = Project::new(String::from("Sprint"));
+    let mut first = Task::new(String::from("First"), String::new());
+    first.estimate = 3;
+    first.created_at = Some(at_noon(today - Duration::days(2)));
+    first.completed_at = Some(at_noon(today - Duration::days(1)));
+    let mut second = Task::new(String::from("Second"), String::new());
+    second.estimate = 2;
+    second.created_at = Some(at_noon(today - Duration::days(2)));
+    project.completed_tasks.push(first);
+    project.active_tasks.push(second);
+    let burndown = get_burndown(&project, today);
+    assert!(burndown.uses_estimate);
+    assert_eq!(burndown.remaining, vec![5.0, 2.0, 2.0]);
+    let velocity = get_velocity(&project, today, 2);
+    assert_eq!(velocity.iter().map(|(_, c)| c).sum::<u64>(), 1);
+}
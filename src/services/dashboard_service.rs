@@ -0,0 +1,337 @@
+use crate::hierarchy::ProjectHierarchy;
+use crate::storage::Storage;
+use crate::structure::{Project, Task};
+use crate::theme::Theme;
+use crate::ui::{
+    describe_controls, find_action, Drawable, HelpSection, InputMode, InputReceptor, KeyBinding,
+    MouseReceptor,
+};
+use chrono::{Duration, Local, NaiveDate};
+use crossterm::event::{KeyCode, MouseEvent, MouseEventKind};
+use std::io::Stdout;
+use tui::backend::CrosstermBackend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Modifier, Style};
+use tui::text::Text;
+use tui::widgets::{BarChart, Block, Borders, Gauge, List, ListItem, Paragraph};
+use tui::Frame;
+
+/// Number of days shown in the completed tasks chart.
+static COMPLETION_HISTORY_DAYS: i64 = 14;
+static OVERDUE_TASKS_SHOWN: usize = 10;
+
+pub struct DashboardService {
+    /// Projects counted in the statistics, which only include archived ones when asked.
+    projects: Vec<Project>,
+    all_projects: Vec<Project>,
+    include_archived: bool,
+    first_displayed_project: usize,
+    error_message: Option<String>,
+}
+
+/// Counts the tasks completed on each of the `day_count` days ending on `last_day`, oldest first.
+pub fn get_completions_per_day(
+    projects: &[Project],
+    last_day: NaiveDate,
+    day_count: i64,
+) -> Vec<(NaiveDate, u64)> {
+    let mut completions: Vec<(NaiveDate, u64)> = (0..day_count)
+        .rev()
+        .map(|days_ago| (last_day - Duration::days(days_ago), 0))
+        .collect();
+    let completion_days = projects
+        .iter()
+        .flat_map(|p| p.completed_tasks.iter())
+        .filter_map(|t| t.completed_at)
+        .map(|completed_at| completed_at.with_timezone(&Local).date_naive());
+    for completion_day in completion_days {
+        if let Some(day) = completions
+            .iter_mut()
+            .find(|(day, _)| *day == completion_day)
+        {
+            day.1 += 1;
+        }
+    }
+    completions
+}
+
+/// Active tasks whose due date is before `today`, the most overdue first.
+pub fn get_overdue_tasks(projects: &[Project], today: NaiveDate) -> Vec<(String, Task)> {
+    let mut overdue_tasks: Vec<(String, Task)> = projects
+        .iter()
+        .flat_map(|p| {
+            p.active_tasks
+                .iter()
+                .map(move |t| (p.name.clone(), t.clone()))
+        })
+        .filter(|(_, t)| t.due_date.map(|d| d < today) == Some(true))
+        .collect();
+    overdue_tasks.sort_by_key(|(_, t)| t.due_date);
+    overdue_tasks
+}
+
+impl DashboardService {
+    pub fn new(storage: Box<dyn Storage>) -> DashboardService {
+        let (all_projects, error_message) = match storage.load_projects() {
+            Ok(projects) => (projects, None),
+            Err(e) => (vec![], Some(e.to_string())),
+        };
+        let mut dashboard = DashboardService {
+            projects: vec![],
+            all_projects,
+            include_archived: false,
+            first_displayed_project: 0,
+            error_message,
+        };
+        dashboard.select_projects();
+        dashboard
+    }
+
+    fn select_projects(&mut self) {
+        let include_archived = self.include_archived;
+        self.projects = self
+            .all_projects
+            .iter()
+            .filter(|p| include_archived || !p.archived)
+            .cloned()
+            .collect();
+        self.first_displayed_project = 0;
+    }
+
+    fn get_summary(&self) -> String {
+        let active_count: usize = self.projects.iter().map(|p| p.active_tasks.len()).sum();
+        let completed_count: usize = self.projects.iter().map(|p| p.completed_tasks.len()).sum();
+        let all_tasks = || {
+            self.projects
+                .iter()
+                .flat_map(|p| p.active_tasks.iter().chain(p.completed_tasks.iter()))
+        };
+        let time_spent: i32 = all_tasks().map(|t| t.time_spent).sum();
+        let estimate: i32 = all_tasks().map(|t| t.estimate).sum();
+        let mut summary = format!(
+            "Projects: {}\nActive tasks: {}\nCompleted tasks: {}\nTime spent: {} / estimate: {}",
+            self.projects.len(),
+            active_count,
+            completed_count,
+            time_spent,
+            estimate
+        );
+        let archived_count = self.all_projects.iter().filter(|p| p.archived).count();
+        if archived_count > 0 {
+            summary.push_str(
+                format!(
+                    "\nArchived projects: {} {}",
+                    archived_count,
+                    match self.include_archived {
+                        true => "included",
+                        false => "hidden",
+                    }
+                )
+                .as_str(),
+            );
+        }
+        if let Some(message) = &self.error_message {
+            summary.push_str(format!("\n\nError while loading projects: {}", message).as_str());
+        }
+        summary
+    }
+
+    fn display_project_gauges(
+        &self,
+        frame: &mut Frame<CrosstermBackend<Stdout>>,
+        layout: Rect,
+        theme: &Theme,
+    ) {
+        let block = Block::default()
+            .title("Completion per project")
+            .borders(Borders::ALL);
+        let gauges_area = block.inner(layout);
+        frame.render_widget(block, layout);
+        // Parents count the tasks of their sub-projects as well.
+        let hierarchy = ProjectHierarchy::from_projects(&self.projects);
+        let displayed_projects: Vec<&Project> = self
+            .projects
+            .iter()
+            .skip(self.first_displayed_project)
+            .take(gauges_area.height as usize)
+            .collect();
+        for (row, project) in displayed_projects.iter().enumerate() {
+            let (completed_count, task_count) = hierarchy.get_task_counts(project.name.as_str());
+            let ratio = match task_count {
+                0 => 0.0,
+                _ => completed_count as f64 / task_count as f64,
+            };
+            let gauge = Gauge::default()
+                .gauge_style(theme.gauge_style())
+                .ratio(ratio)
+                .label(format!(
+                    "{}: {}/{} completed",
+                    project.name, completed_count, task_count
+                ));
+            let gauge_area = Rect::new(
+                gauges_area.x,
+                gauges_area.y + row as u16,
+                gauges_area.width,
+                1,
+            );
+            frame.render_widget(gauge, gauge_area);
+        }
+    }
+}
+
+impl Drawable for DashboardService {
+    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect, theme: &Theme) {
+        let today = Local::now().date_naive();
+        let main_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+            .split(layout);
+        let upper_layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+            .split(main_layout[0]);
+        let lower_layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(main_layout[1]);
+
+        let summary_block = Block::default().title("Overview").borders(Borders::ALL);
+        let summary = Paragraph::new(Text::from(self.get_summary())).block(summary_block);
+        frame.render_widget(summary, upper_layout[0]);
+
+        let completions = get_completions_per_day(&self.projects, today, COMPLETION_HISTORY_DAYS);
+        let labels: Vec<String> = completions
+            .iter()
+            .map(|(day, _)| day.format("%d").to_string())
+            .collect();
+        let bar_data: Vec<(&str, u64)> = labels
+            .iter()
+            .zip(completions.iter())
+            .map(|(label, (_, count))| (label.as_str(), *count))
+            .collect();
+        // Bars and their one column gap share the inside of the block
+        let bar_width = (upper_layout[1].width.saturating_sub(2) / COMPLETION_HISTORY_DAYS as u16)
+            .saturating_sub(1)
+            .max(1);
+        let completions_chart = BarChart::default()
+            .block(
+                Block::default()
+                    .title("Tasks completed per day")
+                    .borders(Borders::ALL),
+            )
+            .data(&bar_data)
+            .bar_width(bar_width)
+            .bar_style(theme.accent_style())
+            .value_style(theme.accent_value_style());
+        frame.render_widget(completions_chart, upper_layout[1]);
+
+        self.display_project_gauges(frame, lower_layout[0], theme);
+
+        let overdue_items: Vec<ListItem> = get_overdue_tasks(&self.projects, today)
+            .into_iter()
+            .take(OVERDUE_TASKS_SHOWN)
+            .map(|(project_name, task)| {
+                let days_late = (today - task.due_date.unwrap()).num_days();
+                ListItem::new(Text::from(format!(
+                    "{} days late | {}: {}",
+                    days_late, project_name, task.name
+                )))
+            })
+            .collect();
+        let overdue_list = List::new(overdue_items)
+            .block(Block::default().title("Most overdue").borders(Borders::ALL))
+            .style(Style::default().add_modifier(Modifier::BOLD));
+        frame.render_widget(overdue_list, lower_layout[1]);
+    }
+}
+
+impl MouseReceptor for DashboardService {
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
+        match mouse_event.kind {
+            MouseEventKind::ScrollUp => self.handle_input_key(KeyCode::Up),
+            MouseEventKind::ScrollDown => self.handle_input_key(KeyCode::Down),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum DashboardAction {
+    ScrollUp,
+    ScrollDown,
+    ToggleArchived,
+}
+
+static DASHBOARD_BINDINGS: &[KeyBinding<DashboardAction>] = &[
+    KeyBinding::new(
+        &[KeyCode::Up],
+        "Up",
+        "Scroll projects up",
+        DashboardAction::ScrollUp,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Down],
+        "Down",
+        "Scroll projects down",
+        DashboardAction::ScrollDown,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('h')],
+        "H",
+        "Include archived",
+        DashboardAction::ToggleArchived,
+    ),
+];
+
+impl InputReceptor for DashboardService {
+    fn handle_input_key(&mut self, key_code: KeyCode) {
+        match find_action(DASHBOARD_BINDINGS, key_code) {
+            Some(DashboardAction::ScrollUp) => {
+                self.first_displayed_project = self.first_displayed_project.saturating_sub(1);
+            }
+            Some(DashboardAction::ScrollDown)
+                if self.first_displayed_project + 1 < self.projects.len() =>
+            {
+                self.first_displayed_project += 1;
+            }
+            Some(DashboardAction::ToggleArchived) => {
+                self.include_archived = !self.include_archived;
+                self.select_projects();
+            }
+            _ => {}
+        }
+    }
+
+    fn get_controls_description(&self) -> String {
+        describe_controls(DASHBOARD_BINDINGS)
+    }
+
+    fn get_help_sections(&self) -> Vec<HelpSection> {
+        vec![HelpSection::from_bindings("Statistics", DASHBOARD_BINDINGS)]
+    }
+
+    fn get_input_mode(&self) -> InputMode {
+        InputMode::CommandMode
+    }
+}
+
+#[test]
+fn completions_are_counted_per_day() {
+    let today = NaiveDate::from_ymd_opt(2022, 3, 10).unwrap();
+    let mut project = Project::new(String::from("Work"));
+    let mut task = Task::new(String::from("Done"), String::new());
+    task.completed_at = Some(
+        today
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap()
+            .with_timezone(&chrono::Utc),
+    );
+    project.completed_tasks.push(task);
+    let completions = get_completions_per_day(&[project], today, 3);
+    assert_eq!(completions.len(), 3);
+    assert_eq!(completions[2], (today, 1));
+    assert_eq!(completions[0].1, 0);
+}
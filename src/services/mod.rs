@@ -1,6 +1,10 @@
+pub mod chart_service;
+pub mod dashboard_service;
 pub mod project_service;
 pub mod task_service;
 
+use crate::storage::Storage;
+
 pub trait Service {
-    fn set_working_directory(&mut self, path: std::path::PathBuf);
+    fn set_storage(&mut self, storage: Box<dyn Storage>);
 }
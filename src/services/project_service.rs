@@ -1,53 +1,86 @@
+use crate::activity::{ActivityEntry, ActivityKind};
+use crate::formats;
+use crate::hierarchy::{ProjectHierarchy, ProjectTreeRow};
+use crate::services;
+use crate::storage::Storage;
 use crate::structure::Project;
+use crate::templates;
+use crate::theme::Theme;
 use crate::ui::{
-    Completable, DisplayList, Drawable, InputMode, InputReceptor, InputReturn, PopupBinaryChoice,
+    describe_controls, find_action, Clickable, Completable, DisplayList, Drawable, HelpSection,
+    InputMode, InputReceptor, InputReturn, KeyBinding, MouseReceptor, PopupBinaryChoice,
     PopupInputWindow, PopupMessageWindow,
 };
-use crate::{services, utils};
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use std::collections::{HashMap, HashSet};
 use std::io::{Error, Stdout};
 use std::ops::Add;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tui::backend::CrosstermBackend;
 use tui::layout::{Constraint, Direction, Layout, Rect};
 use tui::text::Text;
 use tui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
 use tui::Frame;
 
+/// Longest time between the two clicks of a double click.
+static DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+
 enum ProjectInputType {
+    TemplateChoice,
+    TemplateVariable,
     ProjectAdd,
     ProjectDescriptionEdit,
     ProjectNameEdit,
+    ParentEdit,
+    ExportSelected,
+    ExportAll,
+    Import,
 }
 
 pub struct ProjectManagementService<'a> {
     // Everything that is contained in the draw call for the main window
-    projects_to_display: DisplayList<Project>,
+    projects: Vec<Project>,
+    projects_to_display: DisplayList<ProjectTreeRow>,
+    collapsed_projects: HashSet<String>,
     selected_project_active_tasks: Vec<ListItem<'a>>,
     selected_project_completed_tasks: Vec<ListItem<'a>>,
     project_input_popup: PopupInputWindow,
     input_mode: InputMode,
     input_type: ProjectInputType,
-    program_work_path: PathBuf,
+    storage: Box<dyn Storage>,
     message_popup: PopupMessageWindow,
     delete_project_popup: PopupBinaryChoice,
+    show_archived: bool,
+    /// Template the project being added is created from, with the variable values given so far.
+    template: Option<Project>,
+    template_values: HashMap<String, String>,
+    /// When and on which line the list was last clicked, to recognise double clicks.
+    last_click: Option<(Instant, usize)>,
+    open_requested: bool,
 }
 
 impl<'a> ProjectManagementService<'a> {
-    pub fn new(working_path: PathBuf) -> ProjectManagementService<'a> {
+    pub fn new(storage: Box<dyn Storage>) -> ProjectManagementService<'a> {
         let mut project_window = ProjectManagementService {
-            projects_to_display: DisplayList::from(utils::get_projects_in_path(
-                working_path.clone(),
-            )),
+            projects: vec![],
+            projects_to_display: DisplayList::default(),
+            collapsed_projects: HashSet::new(),
             selected_project_active_tasks: Vec::new(),
             selected_project_completed_tasks: Vec::new(),
             project_input_popup: PopupInputWindow::default(),
             input_mode: InputMode::CommandMode,
             input_type: ProjectInputType::ProjectAdd,
-            program_work_path: working_path,
+            storage,
             message_popup: PopupMessageWindow::default(),
             delete_project_popup: PopupBinaryChoice::default(),
+            show_archived: false,
+            template: None,
+            template_values: HashMap::new(),
+            last_click: None,
+            open_requested: false,
         };
+        project_window.reload_projects();
         if project_window.projects_to_display.array.len() > 0 {
             project_window.update_project_selection();
         }
@@ -56,28 +89,88 @@ impl<'a> ProjectManagementService<'a> {
     }
 
     fn update_projects(&mut self, projects: Vec<Project>) {
-        self.projects_to_display = DisplayList::from(projects);
+        self.projects = projects;
+        self.projects_to_display = DisplayList::from(
+            ProjectHierarchy::from_projects(&self.projects).get_rows(&self.collapsed_projects),
+        );
+    }
+
+    /// Collapses or expands the sub-projects of the selected row, which stays selected.
+    fn set_selection_collapsed(&mut self, collapsed: bool) {
+        let row = match self.projects_to_display.state.selected() {
+            Some(selected) => self.projects_to_display.array[selected].clone(),
+            None => return,
+        };
+        if !row.has_children || row.collapsed == collapsed {
+            return;
+        }
+        match collapsed {
+            true => self.collapsed_projects.insert(row.name.clone()),
+            false => self.collapsed_projects.remove(&row.name),
+        };
+        self.update_projects(self.projects.clone());
+        let position = self
+            .projects_to_display
+            .array
+            .iter()
+            .position(|r| r.name == row.name);
+        self.projects_to_display.state.select(position);
+    }
+
+    fn get_selected_project(&self) -> Option<&Project> {
+        self.projects_to_display
+            .state
+            .selected()
+            .and_then(|selected| self.projects_to_display.array.get(selected))
+            .and_then(|row| row.project.as_ref())
     }
 
     fn reload_projects(&mut self) {
-        self.update_projects(utils::get_projects_in_path(self.program_work_path.clone()));
+        match self.storage.load_projects() {
+            Ok(projects) => {
+                let show_archived = self.show_archived;
+                self.update_projects(
+                    projects
+                        .into_iter()
+                        .filter(|p| show_archived || !p.archived)
+                        .collect(),
+                )
+            }
+            Err(e) => {
+                self.update_projects(vec![]);
+                self.create_popup_with_message(e.to_string());
+                self.input_mode = InputMode::WriteMode;
+            }
+        }
+    }
+
+    fn reload_projects_and_selection(&mut self) {
+        self.reload_projects();
+        match self.projects_to_display.array.is_empty() {
+            true => {
+                self.selected_project_active_tasks.clear();
+                self.selected_project_completed_tasks.clear();
+            }
+            false => self.update_project_selection(),
+        }
     }
 
     fn update_project_selection(&mut self) {
-        self.selected_project_active_tasks = self.projects_to_display.array
-            [self.projects_to_display.state.selected().unwrap()]
-        .active_tasks
-        .clone()
-        .into_iter()
-        .map(|a| ListItem::new(Text::from(a.name)))
-        .collect();
-        self.selected_project_completed_tasks = self.projects_to_display.array
-            [self.projects_to_display.state.selected().unwrap()]
-        .completed_tasks
-        .clone()
-        .into_iter()
-        .map(|a| ListItem::new(Text::from(a.name)))
-        .collect();
+        let (active_tasks, completed_tasks) = match self.get_selected_project() {
+            Some(project) => (
+                project.active_tasks.clone(),
+                project.completed_tasks.clone(),
+            ),
+            None => (vec![], vec![]),
+        };
+        self.selected_project_active_tasks = active_tasks
+            .into_iter()
+            .map(|a| ListItem::new(Text::from(a.name)))
+            .collect();
+        self.selected_project_completed_tasks = completed_tasks
+            .into_iter()
+            .map(|a| ListItem::new(Text::from(a.name)))
+            .collect();
     }
 
     fn create_popup_with_message(&mut self, message: String) {
@@ -86,18 +179,66 @@ impl<'a> ProjectManagementService<'a> {
 
     fn add_project_request(&mut self) {
         self.input_mode = InputMode::WriteMode;
+        self.template = None;
+        self.template_values.clear();
+        let template_names = templates::list_templates(&self.get_templates_folder());
+        match template_names.is_empty() {
+            true => self.ask_project_name(String::new()),
+            false => {
+                self.input_type = ProjectInputType::TemplateChoice;
+                self.project_input_popup = PopupInputWindow::new(format!(
+                    "Template ({}) or empty for a blank project",
+                    template_names.join(", ")
+                ));
+            }
+        }
+    }
+
+    fn ask_project_name(&mut self, default_name: String) {
         self.input_type = ProjectInputType::ProjectAdd;
         self.project_input_popup = PopupInputWindow::new(String::from("Insert project name"));
+        self.project_input_popup.set_input_string(default_name);
+    }
+
+    fn get_templates_folder(&self) -> PathBuf {
+        templates::get_templates_folder(self.storage.get_location().as_path())
+    }
+
+    /// Asks for the value of the next variable of the template, then for the project name.
+    fn ask_next_template_value(&mut self) {
+        match self.get_asked_template_variable() {
+            Some(variable) => {
+                self.input_type = ProjectInputType::TemplateVariable;
+                self.project_input_popup =
+                    PopupInputWindow::new(format!("Value of {{{{{}}}}}", variable));
+            }
+            None => {
+                let default_name = match &self.template {
+                    Some(template) => {
+                        templates::fill_placeholders(&template.name, &self.template_values)
+                    }
+                    None => String::new(),
+                };
+                self.ask_project_name(default_name);
+            }
+        }
+    }
+
+    /// The first variable of the template without a value yet.
+    fn get_asked_template_variable(&self) -> Option<String> {
+        let template = self.template.as_ref()?;
+        templates::get_variables(template)
+            .into_iter()
+            .find(|variable| !self.template_values.contains_key(variable))
     }
 
-    fn write_project_to_disk(&self, project_to_write: Project) -> Result<(), Error> {
-        let mut project_file_path = self.program_work_path.join(project_to_write.name.clone());
-        project_file_path.set_extension(utils::PROJECT_FILE_EXTENSION);
-        project_to_write.write_project_full_path(project_file_path)
+    fn write_project_to_disk(&self, mut project_to_write: Project) -> Result<(), Error> {
+        project_to_write.mark_updated();
+        self.storage.save_project(&project_to_write)
     }
 
     fn delete_selected_project(&mut self) {
-        if self.projects_to_display.array.len() > 0 {
+        if self.get_selected_project().is_some() {
             let popup_description =
                 String::from("Delete project: ").add(self.get_selected_project_name().as_str());
             self.delete_project_popup = PopupBinaryChoice::new(popup_description);
@@ -106,29 +247,141 @@ impl<'a> ProjectManagementService<'a> {
     }
 
     fn edit_selected_project_name(&mut self) {
-        if self.projects_to_display.array.len() > 0 {
+        if let Some(project) = self.get_selected_project() {
+            let name = project.name.clone();
             self.input_mode = InputMode::WriteMode;
             self.input_type = ProjectInputType::ProjectNameEdit;
             self.project_input_popup = PopupInputWindow::new(String::from("Edit project name"));
-            self.project_input_popup.set_input_string(
-                self.projects_to_display.array[self.projects_to_display.state.selected().unwrap()]
-                    .name
-                    .clone(),
-            );
+            self.project_input_popup.set_input_string(name);
+        }
+    }
+
+    fn edit_selected_project_parent(&mut self) {
+        if let Some(project) = self.get_selected_project() {
+            let parent = project.parent.clone().unwrap_or_default();
+            self.input_mode = InputMode::WriteMode;
+            self.input_type = ProjectInputType::ParentEdit;
+            self.project_input_popup =
+                PopupInputWindow::new(String::from("Parent project or group (empty for none)"));
+            self.project_input_popup.set_input_string(parent);
         }
     }
 
     fn edit_selected_project_description(&mut self) {
-        if self.projects_to_display.array.len() > 0 {
+        if let Some(project) = self.get_selected_project() {
+            let description = project.description.clone();
             self.input_mode = InputMode::WriteMode;
             self.input_type = ProjectInputType::ProjectDescriptionEdit;
             self.project_input_popup =
                 PopupInputWindow::new(String::from("Edit project description"));
-            self.project_input_popup.set_input_string(
-                self.projects_to_display.array[self.projects_to_display.state.selected().unwrap()]
-                    .description
-                    .clone(),
+            self.project_input_popup.set_input_string(description);
+        }
+    }
+
+    fn toggle_selected_project_archived(&mut self) {
+        if let Some(project) = self.get_selected_project() {
+            let mut project = project.clone();
+            project.archived = !project.archived;
+            let archive_entry = ActivityEntry::new(
+                match project.archived {
+                    true => ActivityKind::ArchiveProject,
+                    false => ActivityKind::UnarchiveProject,
+                },
+                project.name.clone(),
+                None,
+                None,
             );
+            let project_name = project.name.clone();
+            let result = self
+                .write_project_to_disk(project)
+                .and_then(|()| self.storage.append_activity(project_name, &archive_entry));
+            self.reload_projects_and_selection();
+            if let Err(e) = result {
+                self.create_popup_with_message(e.to_string());
+                self.input_mode = InputMode::WriteMode;
+            }
+        }
+    }
+
+    fn toggle_show_archived(&mut self) {
+        self.show_archived = !self.show_archived;
+        self.reload_projects_and_selection();
+    }
+
+    fn get_default_file_path(file_name: String) -> String {
+        let folder = std::env::current_dir().unwrap_or_default();
+        String::from(folder.join(file_name).to_str().unwrap())
+    }
+
+    fn export_request(&mut self, export_all: bool) {
+        if !self.projects_to_display.array.is_empty() {
+            self.input_mode = InputMode::WriteMode;
+            let file_name = match export_all {
+                true => {
+                    self.input_type = ProjectInputType::ExportAll;
+                    String::from("projects.csv")
+                }
+                false => {
+                    self.input_type = ProjectInputType::ExportSelected;
+                    self.get_selected_project_name().add(".csv")
+                }
+            };
+            self.project_input_popup = PopupInputWindow::new(format!(
+                "Export to file ({})",
+                formats::describe_file_extensions(false)
+            ));
+            self.project_input_popup
+                .set_input_string(Self::get_default_file_path(file_name));
+        }
+    }
+
+    fn import_request(&mut self) {
+        self.input_mode = InputMode::WriteMode;
+        self.input_type = ProjectInputType::Import;
+        self.project_input_popup = PopupInputWindow::new(format!(
+            "Import file ({})",
+            formats::describe_file_extensions(true)
+        ));
+        self.project_input_popup
+            .set_input_string(Self::get_default_file_path(String::new()));
+    }
+
+    fn export_to_file(&self, file_path: PathBuf) -> Result<String, Error> {
+        let format = Self::get_file_format(&file_path)?;
+        let projects = match (&self.input_type, self.get_selected_project()) {
+            (ProjectInputType::ExportSelected, Some(project)) => vec![project.clone()],
+            // A group exports every project it contains.
+            (ProjectInputType::ExportSelected, None) => {
+                let group = self.get_selected_project_name();
+                let hierarchy = ProjectHierarchy::from_projects(&self.projects);
+                self.projects
+                    .iter()
+                    .filter(|p| hierarchy.is_within(p.name.as_str(), group.as_str()))
+                    .cloned()
+                    .collect()
+            }
+            _ => self.projects.clone(),
+        };
+        let message = formats::export_file(&projects, &file_path, &format)?;
+        formats::save_task_ids(self.storage.as_ref(), &projects, &format)?;
+        Ok(message)
+    }
+
+    fn import_from_file(&self, file_path: PathBuf) -> Result<String, Error> {
+        let format = Self::get_file_format(&file_path)?;
+        formats::import_file(self.storage.as_ref(), &file_path, &format, None)
+    }
+
+    fn get_file_format(file_path: &Path) -> Result<formats::Format, Error> {
+        match formats::get_format_from_path(file_path) {
+            Some(format) => Ok(format),
+            None => Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Unknown file format, use {}",
+                    formats::describe_file_extensions(false)
+                ),
+            )),
         }
     }
 
@@ -138,39 +391,209 @@ impl<'a> ProjectManagementService<'a> {
             .name
     }
 
+    /// Whether the selected project was double clicked since the last call, to open its tasks.
+    pub fn take_open_request(&mut self) -> bool {
+        std::mem::take(&mut self.open_requested)
+    }
+
+    fn click_project_list(&mut self, column: u16, row: u16) {
+        let index = match self.projects_to_display.get_index_at(column, row) {
+            Some(index) => index,
+            None => return,
+        };
+        let now = Instant::now();
+        let is_double_click = match self.last_click {
+            Some((clicked_at, clicked_index)) => {
+                clicked_index == index && now.duration_since(clicked_at) <= DOUBLE_CLICK_INTERVAL
+            }
+            None => false,
+        };
+        self.last_click = match is_double_click {
+            true => None,
+            false => Some((now, index)),
+        };
+        self.projects_to_display.state.select(Some(index));
+        self.update_project_selection();
+        if is_double_click {
+            // Groups have no tasks, double clicking them folds them instead.
+            match self.get_selected_project() {
+                Some(_) => self.open_requested = true,
+                None => {
+                    let collapsed = !self.projects_to_display.array[index].collapsed;
+                    self.set_selection_collapsed(collapsed);
+                }
+            }
+        }
+    }
+
+    /// The selected project, groups having no tasks to show.
     pub fn get_selected_project_path_name(&self) -> Option<String> {
-        match self.projects_to_display.state.selected() {
-            Some(val) => Option::Some(self.projects_to_display.array[val].clone().name),
-            None => None,
+        self.get_selected_project()
+            .map(|project| project.name.clone())
+    }
+
+    /// Moves the sub-projects of a renamed project to its new name.
+    fn rename_parent_references(&self, original_name: &str, new_name: &str) -> Result<(), Error> {
+        for project in self.projects.iter() {
+            if project.parent.as_deref() == Some(original_name) {
+                let mut sub_project = project.clone();
+                sub_project.parent = Some(new_name.to_string());
+                self.write_project_to_disk(sub_project)?;
+            }
         }
+        Ok(())
     }
 }
 
+#[derive(Clone, Copy)]
+enum ProjectAction {
+    Previous,
+    Next,
+    Collapse,
+    Expand,
+    Add,
+    Delete,
+    EditDescription,
+    EditName,
+    EditParent,
+    ToggleArchived,
+    ToggleShowArchived,
+    ExportSelected,
+    ExportAll,
+    Import,
+}
+
+static PROJECT_BINDINGS: &[KeyBinding<ProjectAction>] = &[
+    KeyBinding::new(
+        &[KeyCode::Up],
+        "Up",
+        "Previous project",
+        ProjectAction::Previous,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Down],
+        "Down",
+        "Next project",
+        ProjectAction::Next,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Left],
+        "Left",
+        "Collapse group",
+        ProjectAction::Collapse,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Right],
+        "Right",
+        "Expand group",
+        ProjectAction::Expand,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('a')],
+        "A",
+        "Add project",
+        ProjectAction::Add,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('d')],
+        "D",
+        "Delete project",
+        ProjectAction::Delete,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('e')],
+        "E",
+        "Edit Project Description",
+        ProjectAction::EditDescription,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('n')],
+        "N",
+        "Edit Project name",
+        ProjectAction::EditName,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('p')],
+        "P",
+        "Set parent",
+        ProjectAction::EditParent,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('r')],
+        "R",
+        "Archive/Unarchive",
+        ProjectAction::ToggleArchived,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('h')],
+        "H",
+        "Show archived",
+        ProjectAction::ToggleShowArchived,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('x')],
+        "X",
+        "Export project",
+        ProjectAction::ExportSelected,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('X')],
+        "Shift-X",
+        "Export all",
+        ProjectAction::ExportAll,
+    ),
+    KeyBinding::new(&[KeyCode::Char('i')], "I", "Import", ProjectAction::Import),
+];
+
 impl<'a> InputReceptor for ProjectManagementService<'a> {
     fn handle_input_key(&mut self, key_code: KeyCode) {
         match self.input_mode {
-            InputMode::CommandMode => match key_code {
-                KeyCode::Up => {
+            InputMode::CommandMode => match find_action(PROJECT_BINDINGS, key_code) {
+                Some(ProjectAction::Previous) => {
                     self.projects_to_display.previous();
                     self.update_project_selection();
                 }
-                KeyCode::Down => {
+                Some(ProjectAction::Next) => {
                     self.projects_to_display.next();
                     self.update_project_selection();
                 }
-                KeyCode::Char('a') => {
+                Some(ProjectAction::Collapse) => {
+                    self.set_selection_collapsed(true);
+                }
+                Some(ProjectAction::Expand) => {
+                    self.set_selection_collapsed(false);
+                }
+                Some(ProjectAction::Add) => {
                     self.add_project_request();
                 }
-                KeyCode::Char('d') => {
+                Some(ProjectAction::Delete) => {
                     self.delete_selected_project();
                 }
-                KeyCode::Char('e') => {
+                Some(ProjectAction::EditDescription) => {
                     self.edit_selected_project_description();
                 }
-                KeyCode::Char('n') => {
+                Some(ProjectAction::EditName) => {
                     self.edit_selected_project_name();
                 }
-                _ => {}
+                Some(ProjectAction::EditParent) => {
+                    self.edit_selected_project_parent();
+                }
+                Some(ProjectAction::ExportSelected) => {
+                    self.export_request(false);
+                }
+                Some(ProjectAction::ExportAll) => {
+                    self.export_request(true);
+                }
+                Some(ProjectAction::Import) => {
+                    self.import_request();
+                }
+                Some(ProjectAction::ToggleArchived) => {
+                    self.toggle_selected_project_archived();
+                }
+                Some(ProjectAction::ToggleShowArchived) => {
+                    self.toggle_show_archived();
+                }
+                None => {}
             },
             InputMode::WriteMode => {
                 if self.message_popup.is_active() {
@@ -184,18 +607,24 @@ impl<'a> InputReceptor for ProjectManagementService<'a> {
                     self.delete_project_popup.handle_input_key(key_code);
                     if self.delete_project_popup.is_completed() {
                         if self.delete_project_popup.get_choice() {
-                            match utils::delete_project_of_name(
-                                self.get_selected_project_name(),
-                                self.program_work_path.clone(),
-                            ) {
+                            let project_name = self.get_selected_project_name();
+                            let delete_entry = ActivityEntry::new(
+                                ActivityKind::DeleteProject,
+                                project_name.clone(),
+                                None,
+                                None,
+                            );
+                            match self
+                                .storage
+                                .append_activity(project_name.clone(), &delete_entry)
+                                .and_then(|()| self.storage.delete_project(project_name))
+                            {
                                 Ok(()) => {}
                                 Err(e) => {
                                     self.create_popup_with_message(e.to_string());
                                 }
                             };
-                            self.update_projects(utils::get_projects_in_path(
-                                self.program_work_path.clone(),
-                            ));
+                            self.reload_projects();
                         }
                         self.delete_project_popup.set_active(false);
                     }
@@ -208,10 +637,45 @@ impl<'a> InputReceptor for ProjectManagementService<'a> {
 
         if self.project_input_popup.is_active() & self.project_input_popup.is_completed() {
             match self.input_type {
+                ProjectInputType::TemplateChoice => {
+                    let template_name = self.project_input_popup.get_input_data();
+                    let template_name = template_name.trim();
+                    if template_name.is_empty() {
+                        self.ask_project_name(String::new());
+                        return;
+                    }
+                    match templates::load_template(&self.get_templates_folder(), template_name) {
+                        Ok(template) => {
+                            self.template = Some(template);
+                            self.ask_next_template_value();
+                        }
+                        Err(e) => {
+                            self.create_popup_with_message(format!(
+                                "Cannot read template {}: {}",
+                                template_name, e
+                            ));
+                            self.project_input_popup.reset_completion();
+                        }
+                    }
+                }
+                ProjectInputType::TemplateVariable => {
+                    if let Some(variable) = self.get_asked_template_variable() {
+                        let value = self.project_input_popup.get_input_data();
+                        self.template_values.insert(variable, value);
+                    }
+                    self.ask_next_template_value();
+                }
                 ProjectInputType::ProjectAdd => {
-                    let new_project = Project::new(self.project_input_popup.get_input_data());
+                    let project_name = self.project_input_popup.get_input_data();
+                    let new_project = match &self.template {
+                        Some(template) => {
+                            templates::create_project(template, &self.template_values, project_name)
+                        }
+                        None => Project::new(project_name),
+                    };
                     match self.write_project_to_disk(new_project) {
                         Ok(_) => {
+                            self.template = None;
                             self.reload_projects();
                             self.project_input_popup.set_active(false);
                             // self.input_mode = InputMode::CommandMode;
@@ -220,29 +684,42 @@ impl<'a> InputReceptor for ProjectManagementService<'a> {
                             self.create_popup_with_message(
                                 e.to_string()
                                     .add(" With path: ")
-                                    .add(self.program_work_path.clone().to_str().unwrap()),
+                                    .add(self.storage.get_location().to_str().unwrap()),
                             );
                             self.project_input_popup.reset_completion();
-                            return;
                         }
                     };
                 }
                 ProjectInputType::ProjectNameEdit => {
-                    let mut project = self.projects_to_display.array
-                        [self.projects_to_display.state.selected().unwrap()]
-                    .clone();
-                    let mut original_path = self.program_work_path.clone().join(project.name);
-                    original_path.set_extension(utils::PROJECT_FILE_EXTENSION);
-                    let mut new_path = self
-                        .program_work_path
-                        .clone()
-                        .join(self.project_input_popup.get_input_data());
-                    new_path.set_extension(utils::PROJECT_FILE_EXTENSION);
+                    let mut project = self.get_selected_project().unwrap().clone();
+                    let original_name = project.name.clone();
                     project.name = self.project_input_popup.get_input_data();
-                    match std::fs::rename(original_path, new_path) {
+                    match self
+                        .storage
+                        .rename_project(original_name.clone(), project.name.clone())
+                    {
                         Ok(()) => {
+                            let rename_entry = ActivityEntry::new(
+                                ActivityKind::RenameProject,
+                                project.name.clone(),
+                                Some(original_name),
+                                Some(project.name.clone()),
+                            );
+                            let new_name = project.name.clone();
                             match self.write_project_to_disk(project) {
-                                Ok(()) => {}
+                                Ok(()) => {
+                                    let result = self
+                                        .rename_parent_references(
+                                            rename_entry.before.as_ref().unwrap(),
+                                            new_name.as_str(),
+                                        )
+                                        .and_then(|()| {
+                                            self.storage.append_activity(new_name, &rename_entry)
+                                        });
+                                    if let Err(e) = result {
+                                        self.create_popup_with_message(e.to_string());
+                                    }
+                                }
                                 Err(e) => {
                                     self.create_popup_with_message(e.to_string());
                                     self.project_input_popup.reset_completion();
@@ -255,14 +732,38 @@ impl<'a> InputReceptor for ProjectManagementService<'a> {
                         Err(e) => {
                             self.create_popup_with_message(e.to_string());
                             self.project_input_popup.reset_completion();
-                            return;
                         }
                     }
                 }
+                ProjectInputType::ParentEdit => {
+                    let mut project = self.get_selected_project().unwrap().clone();
+                    let parent = self.project_input_popup.get_input_data().trim().to_string();
+                    let hierarchy = ProjectHierarchy::from_projects(&self.projects);
+                    if hierarchy.is_within(parent.as_str(), project.name.as_str()) {
+                        self.create_popup_with_message(format!(
+                            "{} cannot be placed under itself or one of its sub-projects",
+                            project.name
+                        ));
+                        self.project_input_popup.reset_completion();
+                        return;
+                    }
+                    project.parent = match parent.is_empty() {
+                        true => None,
+                        false => Some(parent),
+                    };
+                    match self.write_project_to_disk(project) {
+                        Ok(_) => {
+                            self.reload_projects_and_selection();
+                            self.project_input_popup.set_active(false);
+                        }
+                        Err(e) => {
+                            self.create_popup_with_message(e.to_string());
+                            self.project_input_popup.reset_completion();
+                        }
+                    };
+                }
                 ProjectInputType::ProjectDescriptionEdit => {
-                    let mut project = self.projects_to_display.array
-                        [self.projects_to_display.state.selected().unwrap()]
-                    .clone();
+                    let mut project = self.get_selected_project().unwrap().clone();
                     let new_description = self.project_input_popup.get_input_data();
                     project.description = new_description;
                     match self.write_project_to_disk(project) {
@@ -273,7 +774,36 @@ impl<'a> InputReceptor for ProjectManagementService<'a> {
                         Err(e) => {
                             self.create_popup_with_message(e.to_string());
                             self.project_input_popup.reset_completion();
-                            return;
+                        }
+                    };
+                }
+                ProjectInputType::ExportSelected | ProjectInputType::ExportAll => {
+                    let file_path = PathBuf::from(self.project_input_popup.get_input_data());
+                    match self.export_to_file(file_path) {
+                        Ok(message) => {
+                            self.create_popup_with_message(message);
+                            self.project_input_popup.set_active(false);
+                        }
+                        Err(e) => {
+                            self.create_popup_with_message(e.to_string());
+                            self.project_input_popup.reset_completion();
+                        }
+                    };
+                }
+                ProjectInputType::Import => {
+                    let file_path = PathBuf::from(self.project_input_popup.get_input_data());
+                    match self.import_from_file(file_path) {
+                        Ok(message) => {
+                            self.reload_projects();
+                            if !self.projects_to_display.array.is_empty() {
+                                self.update_project_selection();
+                            }
+                            self.create_popup_with_message(message);
+                            self.project_input_popup.set_active(false);
+                        }
+                        Err(e) => {
+                            self.create_popup_with_message(e.to_string());
+                            self.project_input_popup.reset_completion();
                         }
                     };
                 }
@@ -289,7 +819,21 @@ impl<'a> InputReceptor for ProjectManagementService<'a> {
         } else if self.project_input_popup.is_active() {
             return self.project_input_popup.get_controls_description();
         }
-        String::from("Q: Quit | A: Add project | D: Delete project | E: Edit Project Description | N: Edit Project name | Tab: Go to Tasks")
+        describe_controls(PROJECT_BINDINGS)
+    }
+
+    fn get_help_sections(&self) -> Vec<HelpSection> {
+        let mut sections = if self.message_popup.is_active() {
+            self.message_popup.get_help_sections()
+        } else if self.delete_project_popup.is_active() {
+            self.delete_project_popup.get_help_sections()
+        } else if self.project_input_popup.is_active() {
+            self.project_input_popup.get_help_sections()
+        } else {
+            vec![]
+        };
+        sections.push(HelpSection::from_bindings("Projects", PROJECT_BINDINGS));
+        sections
     }
 
     fn get_input_mode(&self) -> InputMode {
@@ -300,8 +844,48 @@ impl<'a> InputReceptor for ProjectManagementService<'a> {
     }
 }
 
+impl<'a> MouseReceptor for ProjectManagementService<'a> {
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
+        let (column, row) = (mouse_event.column, mouse_event.row);
+        let popup_shown = self.message_popup.is_active()
+            || self.delete_project_popup.is_active()
+            || self.project_input_popup.is_active();
+        if !popup_shown {
+            self.input_mode = InputMode::CommandMode;
+        }
+        match (&self.input_mode, mouse_event.kind) {
+            (InputMode::WriteMode, MouseEventKind::Down(MouseButton::Left)) => {
+                let keys = if self.message_popup.is_active() {
+                    self.message_popup.get_keys_for_click(column, row)
+                } else if self.delete_project_popup.is_active() {
+                    self.delete_project_popup.get_keys_for_click(column, row)
+                } else {
+                    vec![]
+                };
+                for key in keys {
+                    self.handle_input_key(key);
+                }
+            }
+            (InputMode::CommandMode, MouseEventKind::Down(MouseButton::Left)) => {
+                self.click_project_list(column, row);
+            }
+            (InputMode::CommandMode, MouseEventKind::ScrollUp)
+                if self.projects_to_display.is_drawn_at(column, row) =>
+            {
+                self.handle_input_key(KeyCode::Up);
+            }
+            (InputMode::CommandMode, MouseEventKind::ScrollDown)
+                if self.projects_to_display.is_drawn_at(column, row) =>
+            {
+                self.handle_input_key(KeyCode::Down);
+            }
+            _ => {}
+        }
+    }
+}
+
 impl<'a> Drawable for ProjectManagementService<'a> {
-    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect) {
+    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect, theme: &Theme) {
         let main_layout = Layout::default()
             .direction(Direction::Horizontal)
             .margin(1)
@@ -313,39 +897,68 @@ impl<'a> Drawable for ProjectManagementService<'a> {
             .margin(0)
             .constraints([Constraint::Percentage(80), Constraint::Percentage(20)].as_ref())
             .split(main_layout[0]);
-        let block = Block::default().title("Projects").borders(Borders::ALL);
+        let block = Block::default()
+            .title(match self.show_archived {
+                true => "Projects (including archived)",
+                false => "Projects",
+            })
+            .borders(Borders::ALL);
         let p_list = List::new::<Vec<ListItem>>(
             self.projects_to_display
                 .array
-                .clone()
-                .into_iter()
-                .map(|p| ListItem::new(Text::from(p.name)))
+                .iter()
+                .map(|row| match row.project.as_ref().map(|p| p.archived) {
+                    Some(true) => {
+                        ListItem::new(Text::from(row.describe())).style(theme.muted_style())
+                    }
+                    _ => ListItem::new(Text::from(row.describe())),
+                })
                 .collect(),
         )
         .block(block)
-        .highlight_style(
-            tui::style::Style::default()
-                .bg(tui::style::Color::Green)
-                .add_modifier(tui::style::Modifier::BOLD),
-        )
+        .highlight_style(theme.selection_style())
         .highlight_symbol("-> ");
         frame.render_stateful_widget(
             p_list,
             project_layout[0],
             &mut self.projects_to_display.state.clone(),
         );
+        self.projects_to_display.set_drawn(
+            project_layout[0],
+            &vec![1; self.projects_to_display.array.len()],
+            self.projects_to_display.state.selected(),
+        );
 
         let block = Block::default()
             .title("Project description")
             .borders(Borders::ALL);
-        let p_description = match self.projects_to_display.array.len() > 0 {
-            true => Paragraph::new(
-                self.projects_to_display.array[self.projects_to_display.state.selected().unwrap()]
-                    .clone()
-                    .description,
-            )
-            .block(block).wrap(Wrap{trim : false}),
-            false => Paragraph::new("").block(block),
+        let selected_row = self
+            .projects_to_display
+            .state
+            .selected()
+            .and_then(|selected| self.projects_to_display.array.get(selected));
+        let p_description = match selected_row {
+            Some(row) => Paragraph::new({
+                let tasks_description = match row.has_children {
+                    true => format!(
+                        "\nWith sub-projects: {}/{} tasks completed",
+                        row.completed_tasks, row.total_tasks
+                    ),
+                    false => String::new(),
+                };
+                match &row.project {
+                    Some(project) => format!(
+                        "{}{}\n{}",
+                        project.get_timestamps_description(),
+                        tasks_description,
+                        project.description
+                    ),
+                    None => format!("Group {}{}", row.name, tasks_description),
+                }
+            })
+            .block(block)
+            .wrap(Wrap { trim: false }),
+            None => Paragraph::new("").block(block),
         };
         frame.render_widget(p_description, project_layout[1]);
 
@@ -364,19 +977,69 @@ impl<'a> Drawable for ProjectManagementService<'a> {
             List::new(self.selected_project_completed_tasks.clone()).block(block);
         frame.render_widget(completed_tasks_list, task_layout[1]);
         if self.project_input_popup.is_active() {
-            self.project_input_popup.display(frame, layout);
+            self.project_input_popup.display(frame, layout, theme);
         }
         if self.delete_project_popup.is_active() {
-            self.delete_project_popup.display(frame, layout);
+            self.delete_project_popup.display(frame, layout, theme);
         }
         if self.message_popup.is_active() {
-            self.message_popup.display(frame, layout);
+            self.message_popup.display(frame, layout, theme);
         }
     }
 }
 
 impl<'a> services::Service for ProjectManagementService<'a> {
-    fn set_working_directory(&mut self, path: PathBuf) {
-        self.program_work_path = path;
+    fn set_storage(&mut self, storage: Box<dyn Storage>) {
+        self.storage = storage;
+        self.reload_projects();
+        if !self.projects_to_display.array.is_empty() {
+            self.update_project_selection();
+        }
     }
 }
+
+#[test]
+fn archived_projects_are_hidden_until_shown() {
+    use crate::storage::{create_test_folder, json_storage::JsonStorage};
+    let folder = create_test_folder("archive");
+    let storage = JsonStorage::new(folder.clone());
+    for name in ["Alpha", "Beta"].iter() {
+        storage
+            .save_project(&Project::new(String::from(*name)))
+            .unwrap();
+    }
+    let mut service = ProjectManagementService::new(Box::new(storage));
+    let row_names = |service: &ProjectManagementService| {
+        service
+            .projects_to_display
+            .array
+            .iter()
+            .map(|row| row.name.clone())
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(row_names(&service), vec!["Alpha", "Beta"]);
+
+    service.projects_to_display.state.select(Some(0));
+    service.toggle_selected_project_archived();
+    assert_eq!(row_names(&service), vec!["Beta"]);
+    let activity = service
+        .storage
+        .load_activity(String::from("Alpha"))
+        .unwrap();
+    assert!(matches!(activity[0].kind, ActivityKind::ArchiveProject));
+
+    service.toggle_show_archived();
+    assert_eq!(row_names(&service), vec!["Alpha", "Beta"]);
+    service.projects_to_display.state.select(Some(0));
+    service.toggle_selected_project_archived();
+    assert!(
+        !service
+            .storage
+            .load_project(String::from("Alpha"))
+            .unwrap()
+            .archived
+    );
+    service.toggle_show_archived();
+    assert_eq!(row_names(&service), vec!["Alpha", "Beta"]);
+    std::fs::remove_dir_all(folder).unwrap();
+}
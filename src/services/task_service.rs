@@ -1,70 +1,267 @@
-use crate::services::task_service::TaskInputChoice::{AddName, EditDescription};
+use crate::activity::{ActivityEntry, ActivityKind};
+use crate::dependencies::DependencyGraph;
+use crate::recurrence::{self, Recurrence};
+use crate::services::chart_service::ProjectChartService;
+use crate::services::task_service::TaskInputChoice::{
+    AddName, AddNote, EditChecklist, EditDependencies, EditDescription, EditDueDate, EditPriority,
+    EditRecurrence, EditTags, MoveTo, Rename,
+};
 use crate::services::Service;
-use crate::structure::{Project, Task, TaskContainer};
+use crate::storage::Storage;
+use crate::structure::{ChecklistItem, Project, Task, TaskNote};
+use crate::theme::Theme;
 use crate::ui::InputMode::CommandMode;
-use crate::ui::{Completable, DisplayList, Drawable, InputMode, InputReceptor, InputReturn};
-use crate::ui::{PopupInputWindow, PopupMessageWindow};
+use crate::ui::{
+    self, Clickable, MouseReceptor, PopupBinaryChoice, PopupInputWindow, PopupMessageWindow,
+};
+use crate::ui::{
+    describe_controls, find_action, Completable, DisplayList, Drawable, HelpSection, InputMode,
+    InputReceptor, InputReturn, KeyBinding,
+};
 use crate::utils;
-use crossterm::event::KeyCode;
-use std::io::Stdout;
-use std::ops::Add;
-use std::path::PathBuf;
+use chrono::NaiveDate;
+use crossterm::event::{KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::io::{Error, Stdout};
 use tui::backend::CrosstermBackend;
 use tui::layout::Direction::{Horizontal, Vertical};
 use tui::layout::{Constraint, Layout, Rect};
 use tui::text::Text;
 use tui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
 use tui::Frame;
+use uuid::Uuid;
 
 #[derive(SmartDefault)]
 enum TaskInputChoice {
     #[default]
     AddName,
+    Rename,
     EditDescription,
+    AddNote,
+    EditChecklist,
+    EditRecurrence,
+    EditDependencies,
+    MoveTo,
+    EditTags,
+    EditPriority,
+    EditDueDate,
+}
+
+/// The projects as they were before a change of the task window, saved back to undo it.
+struct UndoStep {
+    description: String,
+    projects: Vec<Project>,
+}
+
+/// What the pane under the task lists shows.
+#[derive(SmartDefault, Clone, Copy, PartialEq)]
+enum LowerPane {
+    #[default]
+    Description,
+    Activity,
+    Dependencies,
+}
+
+/// Order of the task lists, the most recent tasks first when sorted by a timestamp.
+#[derive(SmartDefault, Clone, Copy)]
+enum TaskSortOrder {
+    #[default]
+    Manual,
+    Created,
+    Updated,
+    Completed,
+}
+
+impl TaskSortOrder {
+    fn next(self) -> TaskSortOrder {
+        match self {
+            TaskSortOrder::Manual => TaskSortOrder::Created,
+            TaskSortOrder::Created => TaskSortOrder::Updated,
+            TaskSortOrder::Updated => TaskSortOrder::Completed,
+            TaskSortOrder::Completed => TaskSortOrder::Manual,
+        }
+    }
+
+    fn get_title_suffix(self) -> &'static str {
+        match self {
+            TaskSortOrder::Manual => "",
+            TaskSortOrder::Created => " (newest first)",
+            TaskSortOrder::Updated => " (last updated first)",
+            TaskSortOrder::Completed => " (last completed first)",
+        }
+    }
+
+    fn sort(self, tasks: &mut [Task]) {
+        match self {
+            TaskSortOrder::Manual => {}
+            TaskSortOrder::Created => tasks.sort_by_key(|t| Reverse(t.created_at)),
+            TaskSortOrder::Updated => tasks.sort_by_key(|t| Reverse(t.updated_at)),
+            TaskSortOrder::Completed => tasks.sort_by_key(|t| Reverse(t.completed_at)),
+        }
+    }
 }
 
-#[derive(Default)]
 pub struct TaskService {
-    working_path: PathBuf,
+    storage: Box<dyn Storage>,
+    project_name: String,
     selected_project: Project,
     active_tasks_list: DisplayList<Task>,
     completed_tasks_list: DisplayList<Task>,
     focused_on_active: bool,
+    sort_order: TaskSortOrder,
+    activity: Vec<ActivityEntry>,
+    /// Tasks of every project, loaded once and updated with the projects changed from here.
+    dependency_graph: DependencyGraph,
+    lower_pane: LowerPane,
+    lower_pane_scroll: u16,
+    lower_pane_area: Cell<Rect>,
+    /// Item of the checklist of the task under the cursor that is toggled.
+    checklist_cursor: usize,
     input_mode: InputMode,
     input_popup: PopupInputWindow,
     input_popup_type: TaskInputChoice,
     message_popup: PopupMessageWindow,
+    chart_popup: ProjectChartService,
+    delete_tasks_popup: PopupBinaryChoice,
+    /// Tasks of both lists the bulk commands act on, kept by id across reloads and sorting.
+    selected_ids: HashSet<Uuid>,
+    /// Task a range selection started from, with the selection it extends.
+    range_anchor: Option<(Uuid, HashSet<Uuid>)>,
+    undo_steps: Vec<UndoStep>,
 }
 
 impl TaskService {
-    pub fn new(working_path: PathBuf, project_name: String) -> TaskService {
-        let mut project_path = working_path
-            .join(String::from('.').add(utils::PROJECT_FILE_EXTENSION))
-            .with_file_name(project_name);
-        project_path.set_extension(utils::PROJECT_FILE_EXTENSION);
-        let loaded_project = match utils::load_project_from_path(project_path.clone()) {
-            Ok(loaded_project) => loaded_project,
-            Err(_) => Project::default(),
-        };
+    pub fn new(storage: Box<dyn Storage>, project_name: String) -> TaskService {
+        let loaded_project = storage
+            .load_project(project_name.clone())
+            .unwrap_or_default();
+        let activity = storage
+            .load_activity(project_name.clone())
+            .unwrap_or_default();
+        let dependency_graph = load_dependency_graph(storage.as_ref(), &loaded_project);
         TaskService {
-            working_path: project_path,
+            storage,
+            project_name,
             selected_project: loaded_project.clone(),
             active_tasks_list: DisplayList::from(loaded_project.active_tasks.clone()),
             completed_tasks_list: DisplayList::from(loaded_project.completed_tasks.clone()),
             focused_on_active: true,
+            sort_order: TaskSortOrder::Manual,
+            activity,
+            dependency_graph,
+            lower_pane: LowerPane::Description,
+            lower_pane_scroll: 0,
+            lower_pane_area: Cell::default(),
+            checklist_cursor: 0,
             input_mode: InputMode::CommandMode,
             input_popup: PopupInputWindow::default(),
             input_popup_type: TaskInputChoice::AddName,
             message_popup: PopupMessageWindow::default(),
+            chart_popup: ProjectChartService::default(),
+            delete_tasks_popup: PopupBinaryChoice::default(),
+            selected_ids: HashSet::new(),
+            range_anchor: None,
+            undo_steps: vec![],
         }
     }
 
+    fn show_charts(&mut self) {
+        self.input_mode = InputMode::WriteMode;
+        self.chart_popup = ProjectChartService::new(self.selected_project.clone());
+    }
+
     fn add_task_command(&mut self) {
         self.input_popup_type = AddName;
         self.input_mode = InputMode::WriteMode;
         self.input_popup = PopupInputWindow::new(String::from("Enter Task Name"));
     }
 
+    /// Adds an active task, refusing a name another task of the project has.
+    fn add_task_named(&mut self, task_name: String) {
+        let task_name = task_name.trim().to_string();
+        if task_name.is_empty() {
+            self.create_message_popup(String::from("A task needs a name"));
+            self.input_popup.reset_completion();
+            return;
+        }
+        if self.is_task_name_taken(task_name.as_str(), None) {
+            self.create_message_popup(format!("There already is a task named {}", task_name));
+            self.input_popup.reset_completion();
+            return;
+        }
+        let before = vec![self.selected_project.clone()];
+        let add_entry = ActivityEntry::new(ActivityKind::AddTask, task_name.clone(), None, None);
+        let description = format!("add {}", task_name);
+        self.active_tasks_list
+            .array
+            .push(Task::new(task_name, String::from("Description")));
+        self.save_change(description, before, vec![add_entry]);
+    }
+
+    /// Whether a task of either list other than `except` is named `name`.
+    fn is_task_name_taken(&self, name: &str, except: Option<Uuid>) -> bool {
+        self.active_tasks_list
+            .array
+            .iter()
+            .chain(self.completed_tasks_list.array.iter())
+            .any(|task| Some(task.id) != except && task.name == name)
+    }
+
+    fn rename_task_command(&mut self) {
+        if let Some(name) = self.get_selected_task().map(|task| task.name.clone()) {
+            self.input_popup_type = Rename;
+            self.input_mode = InputMode::WriteMode;
+            self.input_popup = PopupInputWindow::new(String::from("Rename task"));
+            self.input_popup.set_input_string(name);
+        }
+    }
+
+    /// Renames the task under the cursor, in whichever list it is, to a name no other task of
+    /// the project has.
+    fn rename_selected_task(&mut self, new_name: String) {
+        let new_name = new_name.trim().to_string();
+        if new_name.is_empty() {
+            self.create_message_popup(String::from("A task needs a name"));
+            self.input_popup.reset_completion();
+            return;
+        }
+        let selected_id = match self.get_selected_task() {
+            Some(task) => task.id,
+            None => return,
+        };
+        if self.is_task_name_taken(new_name.as_str(), Some(selected_id)) {
+            self.create_message_popup(format!("There already is a task named {}", new_name));
+            self.input_popup.reset_completion();
+            return;
+        }
+        let before = vec![self.selected_project.clone()];
+        let list = match self.focused_on_active {
+            true => &mut self.active_tasks_list,
+            false => &mut self.completed_tasks_list,
+        };
+        let task = match list.state.selected() {
+            Some(val) => &mut list.array[val],
+            None => return,
+        };
+        if task.name == new_name {
+            self.input_popup.set_active(false);
+            self.input_mode = CommandMode;
+            return;
+        }
+        let rename_entry = ActivityEntry::new(
+            ActivityKind::RenameTask,
+            new_name.clone(),
+            Some(task.name.clone()),
+            Some(new_name.clone()),
+        );
+        let description = format!("rename {}", task.name);
+        task.name = new_name;
+        task.mark_updated();
+        self.save_change(description, before, vec![rename_entry]);
+    }
+
     fn edit_task_description(&mut self) {
         let input_string = match self.focused_on_active {
             true => match self.active_tasks_list.state.selected() {
@@ -82,74 +279,1090 @@ impl TaskService {
         self.input_popup.set_input_string(input_string);
     }
 
-    fn mark_selected_task_as_completed(&mut self) {
-        match self.active_tasks_list.state.selected() {
-            Some(val) => {
-                self.completed_tasks_list
-                    .array
-                    .push(self.active_tasks_list.array[val].clone());
-                self.active_tasks_list.array.remove(val);
-                self.selected_project.active_tasks = self.active_tasks_list.array.clone();
-                self.selected_project.completed_tasks = self.completed_tasks_list.array.clone();
-                match self
-                    .selected_project
-                    .write_project_full_path(self.working_path.clone())
-                {
-                    Ok(_) => {}
-                    Err(e) => self.create_message_popup(e.to_string()),
-                };
+    /// Replaces the description of the task under the cursor, in whichever list it is.
+    fn set_selected_task_description(&mut self, description: String) {
+        let before = vec![self.selected_project.clone()];
+        let list = match self.focused_on_active {
+            true => &mut self.active_tasks_list,
+            false => &mut self.completed_tasks_list,
+        };
+        let task = match list.state.selected() {
+            Some(val) => &mut list.array[val],
+            None => {
+                self.create_message_popup(String::from("Selected task is invalid"));
+                return;
+            }
+        };
+        let edit_entry = edit_activity(task, description.clone());
+        let change = format!("edit the description of {}", task.name);
+        task.description = description;
+        task.mark_updated();
+        self.save_change(change, before, vec![edit_entry]);
+    }
+
+    fn add_note_command(&mut self) {
+        if let Some(name) = self.get_selected_task().map(|task| task.name.clone()) {
+            self.input_popup_type = AddNote;
+            self.input_mode = InputMode::WriteMode;
+            self.input_popup = PopupInputWindow::new(format!("Note on {}", name));
+        }
+    }
+
+    /// Appends a note to the thread of the task under the cursor.
+    fn add_note_to_selected_task(&mut self, text: String) {
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            self.create_message_popup(String::from("The note is empty"));
+            self.input_popup.reset_completion();
+            return;
+        }
+        let before = vec![self.selected_project.clone()];
+        let list = match self.focused_on_active {
+            true => &mut self.active_tasks_list,
+            false => &mut self.completed_tasks_list,
+        };
+        let task = match list.state.selected() {
+            Some(val) => &mut list.array[val],
+            None => return,
+        };
+        let note_entry = ActivityEntry::new(
+            ActivityKind::EditTask,
+            task.name.clone(),
+            None,
+            Some(format!("note: {}", text)),
+        );
+        let description = format!("add a note to {}", task.name);
+        task.notes.push(TaskNote::new(text));
+        task.mark_updated();
+        self.save_change(description, before, vec![note_entry]);
+    }
+
+    fn edit_checklist_command(&mut self) {
+        if let Some(task) = self.get_selected_task() {
+            let input_string = format_checklist(&task.checklist);
+            self.input_popup_type = EditChecklist;
+            self.input_mode = InputMode::WriteMode;
+            self.input_popup = PopupInputWindow::new(String::from(
+                "Checklist: items separated by semicolons, [x] before those done",
+            ));
+            self.input_popup.set_input_string(input_string);
+        }
+    }
+
+    /// Replaces the checklist of the task under the cursor, in whichever list it is.
+    fn set_selected_task_checklist(&mut self, input: String) {
+        let checklist = parse_checklist(input.as_str());
+        let before = vec![self.selected_project.clone()];
+        let list = match self.focused_on_active {
+            true => &mut self.active_tasks_list,
+            false => &mut self.completed_tasks_list,
+        };
+        let task = match list.state.selected() {
+            Some(val) => &mut list.array[val],
+            None => return,
+        };
+        if task.checklist == checklist {
+            self.input_popup.set_active(false);
+            self.input_mode = CommandMode;
+            return;
+        }
+        let checklist_entry = ActivityEntry::new(
+            ActivityKind::EditTask,
+            task.name.clone(),
+            Some(format!("checklist: {}", format_checklist(&task.checklist))),
+            Some(format!("checklist: {}", format_checklist(&checklist))),
+        );
+        let description = format!("edit the checklist of {}", task.name);
+        task.checklist = checklist;
+        task.mark_updated();
+        self.checklist_cursor = 0;
+        self.save_change(description, before, vec![checklist_entry]);
+    }
+
+    fn move_checklist_cursor(&mut self, forward: bool) {
+        let item_count = self
+            .get_selected_task()
+            .map_or(0, |task| task.checklist.len());
+        self.checklist_cursor = match forward {
+            true => (self.checklist_cursor + 1).min(item_count.saturating_sub(1)),
+            false => self.checklist_cursor.saturating_sub(1),
+        };
+    }
+
+    /// Checks the item under the checklist cursor, or unchecks it.
+    fn toggle_checklist_item(&mut self) {
+        let before = vec![self.selected_project.clone()];
+        let cursor = self.checklist_cursor;
+        let list = match self.focused_on_active {
+            true => &mut self.active_tasks_list,
+            false => &mut self.completed_tasks_list,
+        };
+        let task = match list.state.selected() {
+            Some(val) => &mut list.array[val],
+            None => return,
+        };
+        let item = match task.checklist.get_mut(cursor) {
+            Some(item) => item,
+            None => return,
+        };
+        item.done = !item.done;
+        let (action, description) = match item.done {
+            true => ("checked", format!("check {}", item.text)),
+            false => ("unchecked", format!("uncheck {}", item.text)),
+        };
+        let toggle_entry = ActivityEntry::new(
+            ActivityKind::EditTask,
+            task.name.clone(),
+            None,
+            Some(format!("{}: {}", action, item.text)),
+        );
+        task.mark_updated();
+        let task_id = task.id;
+        // Saving reloads and sorts the lists again, the cursor is put back on the same task.
+        if self.save_change(description, before, vec![toggle_entry]) {
+            let list = match self.focused_on_active {
+                true => &mut self.active_tasks_list,
+                false => &mut self.completed_tasks_list,
+            };
+            if let Some(index) = list.array.iter().position(|task| task.id == task_id) {
+                list.state.select(Some(index));
             }
-            None => {}
+            self.checklist_cursor = cursor;
+        }
+    }
+
+    /// Only active tasks repeat, the completed list ignores the command.
+    fn edit_task_recurrence(&mut self) {
+        let selected = self.active_tasks_list.state.selected();
+        if let Some(val) = selected.filter(|_| self.focused_on_active) {
+            let rule = match &self.active_tasks_list.array[val].recurrence {
+                Some(recurrence) => recurrence.to_rule(),
+                None => String::from("none"),
+            };
+            self.input_popup_type = EditRecurrence;
+            self.input_mode = InputMode::WriteMode;
+            self.input_popup =
+                PopupInputWindow::new(format!("Repeat: {}", recurrence::RULE_EXAMPLES));
+            self.input_popup.set_input_string(rule);
         }
     }
 
-    fn mark_selected_task_as_uncompleted(&mut self) {
-        match self.completed_tasks_list.state.selected() {
-            Some(val) => {
-                self.active_tasks_list
-                    .array
-                    .push(self.completed_tasks_list.array[val].clone());
-                self.completed_tasks_list.array.remove(val);
-                self.selected_project.completed_tasks = self.completed_tasks_list.array.clone();
-                self.selected_project.active_tasks = self.active_tasks_list.array.clone();
-                match self
-                    .selected_project
-                    .write_project_full_path(self.working_path.clone())
+    fn set_selected_task_recurrence(&mut self, rule: String) {
+        let recurrence = match Recurrence::parse(rule.as_str()) {
+            Ok(recurrence) => recurrence,
+            Err(message) => {
+                self.create_message_popup(message);
+                self.input_popup.reset_completion();
+                return;
+            }
+        };
+        let val = match self.active_tasks_list.state.selected() {
+            Some(val) => val,
+            None => return,
+        };
+        let before = vec![self.selected_project.clone()];
+        let task = &mut self.active_tasks_list.array[val];
+        let rule_entry = ActivityEntry::new(
+            ActivityKind::EditTask,
+            task.name.clone(),
+            Some(
+                task.recurrence
+                    .as_ref()
+                    .map_or(String::from("none"), |r| r.to_rule()),
+            ),
+            Some(
+                recurrence
+                    .as_ref()
+                    .map_or(String::from("none"), |r| r.to_rule()),
+            ),
+        );
+        let description = format!("set the recurrence of {}", task.name);
+        task.recurrence = recurrence;
+        task.mark_updated();
+        self.save_change(description, before, vec![rule_entry]);
+    }
+
+    /// The tasks a command acts on: the selected ones of both lists when there are some, the
+    /// task under the cursor otherwise.
+    fn get_target_ids(&self) -> HashSet<Uuid> {
+        match self.selected_ids.is_empty() {
+            true => self
+                .get_selected_task()
+                .map(|task| task.id)
+                .into_iter()
+                .collect(),
+            false => self.selected_ids.clone(),
+        }
+    }
+
+    fn get_target_names(&self) -> Vec<String> {
+        let targets = self.get_target_ids();
+        self.active_tasks_list
+            .array
+            .iter()
+            .chain(self.completed_tasks_list.array.iter())
+            .filter(|task| targets.contains(&task.id))
+            .map(|task| task.name.clone())
+            .collect()
+    }
+
+    fn complete_target_tasks(&mut self) {
+        let targets = self.get_target_ids();
+        let (completed_tasks, active_tasks): (Vec<Task>, Vec<Task>) = self
+            .active_tasks_list
+            .array
+            .iter()
+            .cloned()
+            .partition(|task| targets.contains(&task.id));
+        if completed_tasks.is_empty() {
+            return;
+        }
+        let before = vec![self.selected_project.clone()];
+        let description = describe_change("complete", &completed_tasks);
+        self.active_tasks_list.array = active_tasks;
+        let mut entries: Vec<ActivityEntry> = vec![];
+        let mut completed_ids: Vec<Uuid> = vec![];
+        for mut completed_task in completed_tasks {
+            completed_task.mark_completed();
+            entries.push(ActivityEntry::new(
+                ActivityKind::CompleteTask,
+                completed_task.name.clone(),
+                None,
+                None,
+            ));
+            // The rule moves to the next instance so reopening this one cannot repeat it twice.
+            if let Some(next_task) = completed_task.get_next_occurrence() {
+                entries.push(ActivityEntry::new(
+                    ActivityKind::AddTask,
+                    next_task.name.clone(),
+                    None,
+                    next_task.due_date.map(|d| format!("due {}", d)),
+                ));
+                self.active_tasks_list.array.push(next_task);
+            }
+            completed_task.recurrence = None;
+            completed_ids.push(completed_task.id);
+            self.completed_tasks_list.array.push(completed_task);
+        }
+        if self.save_change(description, before, entries) {
+            self.show_unblocked_tasks(&completed_ids);
+        }
+    }
+
+    fn reopen_target_tasks(&mut self) {
+        let targets = self.get_target_ids();
+        let (reopened_tasks, completed_tasks): (Vec<Task>, Vec<Task>) = self
+            .completed_tasks_list
+            .array
+            .iter()
+            .cloned()
+            .partition(|task| targets.contains(&task.id));
+        if reopened_tasks.is_empty() {
+            return;
+        }
+        let before = vec![self.selected_project.clone()];
+        let description = describe_change("reopen", &reopened_tasks);
+        self.completed_tasks_list.array = completed_tasks;
+        let mut entries: Vec<ActivityEntry> = vec![];
+        for mut reopened_task in reopened_tasks {
+            reopened_task.mark_reopened();
+            entries.push(ActivityEntry::new(
+                ActivityKind::ReopenTask,
+                reopened_task.name.clone(),
+                None,
+                None,
+            ));
+            self.active_tasks_list.array.push(reopened_task);
+        }
+        self.save_change(description, before, entries);
+    }
+
+    fn confirm_delete_target_tasks(&mut self) {
+        let names = self.get_target_names();
+        let message = match names.as_slice() {
+            [] => return,
+            [name] => format!("Delete task: {}", name),
+            _ => format!("Delete {} selected tasks", names.len()),
+        };
+        self.delete_tasks_popup = PopupBinaryChoice::new(message);
+        self.input_mode = InputMode::WriteMode;
+    }
+
+    fn delete_target_tasks(&mut self) {
+        let targets = self.get_target_ids();
+        let before = vec![self.selected_project.clone()];
+        let deleted_tasks = self.take_tasks(&targets);
+        let entries = deleted_tasks
+            .iter()
+            .map(|task| ActivityEntry::new(ActivityKind::DeleteTask, task.name.clone(), None, None))
+            .collect();
+        self.save_change(describe_change("delete", &deleted_tasks), before, entries);
+    }
+
+    /// Removes the tasks of `ids` from both lists, in the order they were shown.
+    fn take_tasks(&mut self, ids: &HashSet<Uuid>) -> Vec<Task> {
+        let mut taken: Vec<Task> = vec![];
+        for list in [&mut self.active_tasks_list, &mut self.completed_tasks_list] {
+            let (removed, kept): (Vec<Task>, Vec<Task>) = list
+                .array
+                .drain(..)
+                .partition(|task| ids.contains(&task.id));
+            list.array = kept;
+            taken.extend(removed);
+        }
+        taken
+    }
+
+    fn move_target_tasks(&mut self, project_name: String) {
+        let project_name = project_name.trim().to_string();
+        if project_name == self.project_name {
+            self.create_message_popup(String::from("The tasks already are in this project"));
+            self.input_popup.reset_completion();
+            return;
+        }
+        let mut target_project = match self.storage.load_project(project_name.clone()) {
+            Ok(project) => project,
+            Err(_) => {
+                self.create_message_popup(format!("There is no project named {}", project_name));
+                self.input_popup.reset_completion();
+                return;
+            }
+        };
+        let before = vec![self.selected_project.clone(), target_project.clone()];
+        let targets = self.get_target_ids();
+        let completed_ids: HashSet<Uuid> = self
+            .completed_tasks_list
+            .array
+            .iter()
+            .map(|task| task.id)
+            .collect();
+        let moved_tasks = self.take_tasks(&targets);
+        let mut entries: Vec<ActivityEntry> = vec![];
+        for mut task in moved_tasks.iter().cloned() {
+            entries.push(ActivityEntry::new(
+                ActivityKind::MoveTask,
+                task.name.clone(),
+                Some(self.project_name.clone()),
+                Some(target_project.name.clone()),
+            ));
+            task.mark_updated();
+            match completed_ids.contains(&task.id) {
+                true => target_project.completed_tasks.push(task),
+                false => target_project.active_tasks.push(task),
+            }
+        }
+        target_project.mark_updated();
+        // The project receiving the tasks is saved first, a failure then loses nothing.
+        if let Err(e) = self.storage.save_project(&target_project) {
+            self.update_project();
+            self.create_message_popup(e.to_string());
+            return;
+        }
+        self.dependency_graph.add_project(&target_project);
+        let description = format!(
+            "{} to {}",
+            describe_change("move", &moved_tasks),
+            target_project.name
+        );
+        if self.save_change(description, before, entries.clone()) {
+            for entry in entries {
+                if let Err(e) = self
+                    .storage
+                    .append_activity(target_project.name.clone(), &entry)
                 {
-                    Ok(_) => {}
-                    Err(e) => self.create_message_popup(e.to_string()),
-                };
+                    self.create_message_popup(e.to_string());
+                }
+            }
+        }
+    }
+
+    /// Applies `edit` to the target tasks, which tells how each one changed, if it did.
+    fn edit_target_tasks(
+        &mut self,
+        action: &str,
+        edit: impl Fn(&mut Task) -> Option<(String, String)>,
+    ) {
+        let targets = self.get_target_ids();
+        let before = vec![self.selected_project.clone()];
+        let mut edited_tasks: Vec<Task> = vec![];
+        let mut entries: Vec<ActivityEntry> = vec![];
+        for task in self
+            .active_tasks_list
+            .array
+            .iter_mut()
+            .chain(self.completed_tasks_list.array.iter_mut())
+            .filter(|task| targets.contains(&task.id))
+        {
+            if let Some((old_value, new_value)) = edit(task) {
+                task.mark_updated();
+                entries.push(ActivityEntry::new(
+                    ActivityKind::EditTask,
+                    task.name.clone(),
+                    Some(old_value),
+                    Some(new_value),
+                ));
+                edited_tasks.push(task.clone());
+            }
+        }
+        match edited_tasks.is_empty() {
+            true => {
+                self.input_popup.set_active(false);
+                self.input_mode = CommandMode;
+            }
+            false => {
+                self.save_change(describe_change(action, &edited_tasks), before, entries);
+            }
+        }
+    }
+
+    fn set_target_tasks_tags(&mut self, input: String) {
+        self.edit_target_tasks("tag", |task| {
+            let tags = edit_tags(&task.tags, input.as_str());
+            if tags == task.tags {
+                return None;
+            }
+            let change = (describe_tags(&task.tags), describe_tags(&tags));
+            task.tags = tags;
+            Some(change)
+        });
+    }
+
+    fn set_target_tasks_priority(&mut self, input: String) {
+        let priority = match parse_priority(input.as_str()) {
+            Ok(priority) => priority,
+            Err(message) => {
+                self.create_message_popup(message);
+                self.input_popup.reset_completion();
+                return;
+            }
+        };
+        let describe = |priority: Option<char>| {
+            format!(
+                "priority: {}",
+                priority.map_or(String::from("none"), String::from)
+            )
+        };
+        self.edit_target_tasks("prioritize", |task| {
+            if task.priority == priority {
+                return None;
+            }
+            let change = (describe(task.priority), describe(priority));
+            task.priority = priority;
+            Some(change)
+        });
+    }
+
+    fn set_target_tasks_due_date(&mut self, input: String) {
+        let due_date = match parse_due_date(input.as_str()) {
+            Ok(due_date) => due_date,
+            Err(message) => {
+                self.create_message_popup(message);
+                self.input_popup.reset_completion();
+                return;
+            }
+        };
+        let describe = |due_date: Option<NaiveDate>| {
+            format!(
+                "due: {}",
+                due_date.map_or(String::from("none"), |d| d.to_string())
+            )
+        };
+        self.edit_target_tasks("reschedule", |task| {
+            if task.due_date == due_date {
+                return None;
+            }
+            let change = (describe(task.due_date), describe(due_date));
+            task.due_date = due_date;
+            Some(change)
+        });
+    }
+
+    fn edit_target_tasks_command(&mut self, input_type: TaskInputChoice) {
+        let names = self.get_target_names();
+        let subject = match names.as_slice() {
+            [] => return,
+            [name] => name.clone(),
+            _ => format!("{} tasks", names.len()),
+        };
+        let (title, input_string) = match input_type {
+            MoveTo => (format!("Move {} to project", subject), String::new()),
+            EditTags => (
+                format!(
+                    "Tags to add to {}, -tag to remove, separated by commas",
+                    subject
+                ),
+                String::new(),
+            ),
+            EditPriority => (
+                format!(
+                    "Priority of {}: a letter from A to Z, empty for none",
+                    subject
+                ),
+                self.get_selected_task()
+                    .and_then(|task| task.priority)
+                    .filter(|_| names.len() == 1)
+                    .map(String::from)
+                    .unwrap_or_default(),
+            ),
+            _ => (
+                format!("Due date of {}: YYYY-MM-DD, empty for none", subject),
+                self.get_selected_task()
+                    .and_then(|task| task.due_date)
+                    .filter(|_| names.len() == 1)
+                    .map(|d| d.to_string())
+                    .unwrap_or_default(),
+            ),
+        };
+        self.input_popup_type = input_type;
+        self.input_mode = InputMode::WriteMode;
+        self.input_popup = PopupInputWindow::new(title);
+        self.input_popup.set_input_string(input_string);
+    }
+
+    /// Saves the lists as the project in a single write and logs `entries`, keeping the
+    /// projects as they were `before` so the whole change can be undone in one step.
+    fn save_change(
+        &mut self,
+        description: String,
+        before: Vec<Project>,
+        entries: Vec<ActivityEntry>,
+    ) -> bool {
+        self.store_task_lists();
+        match self.write_project() {
+            Ok(_) => {
+                for entry in entries {
+                    self.record_activity(entry);
+                }
+                self.undo_steps.push(UndoStep {
+                    description,
+                    projects: before,
+                });
+                self.clear_selection();
+                if self.input_popup.is_active() {
+                    self.input_popup.set_active(false);
+                    self.input_mode = CommandMode;
+                }
+                self.update_project();
+                true
+            }
+            Err(e) => {
+                self.update_project();
+                self.create_message_popup(e.to_string());
+                false
+            }
+        }
+    }
+
+    fn undo_last_change(&mut self) {
+        let step = match self.undo_steps.pop() {
+            Some(step) => step,
+            None => {
+                self.create_message_popup(String::from("Nothing to undo"));
+                return;
+            }
+        };
+        for project in step.projects.iter() {
+            if let Err(e) = self.storage.save_project(project) {
+                self.update_project();
+                self.create_message_popup(e.to_string());
+                return;
+            }
+            self.dependency_graph.add_project(project);
+        }
+        self.record_activity(ActivityEntry::new(
+            ActivityKind::UndoChange,
+            step.description,
+            None,
+            None,
+        ));
+        self.clear_selection();
+        self.update_project();
+    }
+
+    fn get_focused_list(&self) -> &DisplayList<Task> {
+        match self.focused_on_active {
+            true => &self.active_tasks_list,
+            false => &self.completed_tasks_list,
+        }
+    }
+
+    fn toggle_selected_task(&mut self) {
+        if let Some(id) = self.get_selected_task().map(|task| task.id) {
+            if !self.selected_ids.remove(&id) {
+                self.selected_ids.insert(id);
+            }
+            self.range_anchor = None;
+        }
+    }
+
+    /// Starts selecting the tasks between the current one and the cursor, or stops it.
+    fn toggle_range_selection(&mut self) {
+        self.range_anchor = match (&self.range_anchor, self.get_selected_task()) {
+            (None, Some(task)) => Some((task.id, self.selected_ids.clone())),
+            _ => None,
+        };
+        self.extend_range_selection();
+    }
+
+    fn extend_range_selection(&mut self) {
+        let (anchor, base) = match &self.range_anchor {
+            Some(range) => range,
+            None => return,
+        };
+        let list = self.get_focused_list();
+        let anchor_index = list.array.iter().position(|task| &task.id == anchor);
+        if let (Some(anchor_index), Some(cursor)) = (anchor_index, list.state.selected()) {
+            let range = anchor_index.min(cursor)..=anchor_index.max(cursor);
+            let mut selection = base.clone();
+            selection.extend(list.array[range].iter().map(|task| task.id));
+            self.selected_ids = selection;
+        }
+    }
+
+    fn clear_selection(&mut self) {
+        self.selected_ids.clear();
+        self.range_anchor = None;
+    }
+
+    fn get_selection_title_suffix(&self, tasks: &[Task]) -> String {
+        match tasks
+            .iter()
+            .filter(|t| self.selected_ids.contains(&t.id))
+            .count()
+        {
+            0 => String::new(),
+            count => format!(" ({} selected)", count),
+        }
+    }
+
+    pub fn get_project_location(&self, project_name: String) -> String {
+        self.storage.get_project_location(project_name)
+    }
+
+    fn write_project(&mut self) -> Result<(), Error> {
+        self.selected_project.mark_updated();
+        self.storage.save_project(&self.selected_project)
+    }
+
+    /// Appends to the activity log once the change it describes has been saved.
+    fn record_activity(&mut self, entry: ActivityEntry) {
+        match self
+            .storage
+            .append_activity(self.project_name.clone(), &entry)
+        {
+            Ok(()) => self.activity.push(entry),
+            Err(e) => self.create_message_popup(e.to_string()),
+        }
+    }
+
+    fn toggle_lower_pane(&mut self, pane: LowerPane) {
+        self.lower_pane = match self.lower_pane == pane {
+            true => LowerPane::Description,
+            false => pane,
+        };
+        self.lower_pane_scroll = 0;
+    }
+
+    fn get_selected_task(&self) -> Option<&Task> {
+        match self.focused_on_active {
+            true => self
+                .active_tasks_list
+                .state
+                .selected()
+                .map(|val| &self.active_tasks_list.array[val]),
+            false => self
+                .completed_tasks_list
+                .state
+                .selected()
+                .map(|val| &self.completed_tasks_list.array[val]),
+        }
+    }
+
+    /// Only active tasks wait for others, the completed list ignores the command.
+    fn edit_task_dependencies(&mut self) {
+        let selected = self.active_tasks_list.state.selected();
+        if let Some(val) = selected.filter(|_| self.focused_on_active) {
+            let references: Vec<String> = self.active_tasks_list.array[val]
+                .depends_on
+                .iter()
+                .map(|id| {
+                    self.dependency_graph
+                        .get_reference(id, self.project_name.as_str())
+                })
+                .collect();
+            self.input_popup_type = EditDependencies;
+            self.input_mode = InputMode::WriteMode;
+            self.input_popup = PopupInputWindow::new(String::from(
+                "Depends on: task names, project/task or ids, separated by commas",
+            ));
+            self.input_popup.set_input_string(references.join(", "));
+        }
+    }
+
+    /// Replaces the dependencies of the selected task, rejecting unknown tasks and cycles.
+    fn set_selected_task_dependencies(&mut self, input: String) {
+        let val = match self.active_tasks_list.state.selected() {
+            Some(val) => val,
+            None => return,
+        };
+        // Tasks saved before they had an id get a new one on every load, so the projects
+        // referenced are saved with the ids the references were resolved to.
+        let mut projects = match self.storage.load_projects() {
+            Ok(projects) => projects,
+            Err(e) => {
+                self.create_message_popup(e.to_string());
+                return;
             }
-            None => {}
+        };
+        projects.retain(|p| p.name != self.project_name);
+        projects.push(self.selected_project.clone());
+        let graph = DependencyGraph::from_projects(&projects);
+        let mut dependencies: Vec<Uuid> = vec![];
+        for reference in input.split(',').filter(|r| !r.trim().is_empty()) {
+            match graph.find_task(reference, self.project_name.as_str()) {
+                Ok(id) if !dependencies.contains(&id) => dependencies.push(id),
+                Ok(_) => {}
+                Err(message) => {
+                    self.create_message_popup(message);
+                    self.input_popup.reset_completion();
+                    return;
+                }
+            }
+        }
+        let task = &self.active_tasks_list.array[val];
+        if graph.creates_cycle(&task.id, &dependencies) {
+            self.create_message_popup(format!(
+                "{} cannot depend on a task that already waits for it",
+                task.name
+            ));
+            self.input_popup.reset_completion();
+            return;
         }
+        let describe = |ids: &[Uuid]| {
+            ids.iter()
+                .map(|id| graph.get_reference(id, self.project_name.as_str()))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let dependency_entry = ActivityEntry::new(
+            ActivityKind::EditTask,
+            task.name.clone(),
+            Some(format!("depends on: {}", describe(&task.depends_on))),
+            Some(format!("depends on: {}", describe(&dependencies))),
+        );
+        for project in projects.iter().filter(|p| p.name != self.project_name) {
+            let is_referenced = project
+                .active_tasks
+                .iter()
+                .chain(project.completed_tasks.iter())
+                .any(|t| dependencies.contains(&t.id));
+            if is_referenced {
+                if let Err(e) = self.storage.save_project(project) {
+                    self.create_message_popup(e.to_string());
+                    return;
+                }
+            }
+        }
+        // The graph was built from the projects as just saved, it replaces the cached one.
+        self.dependency_graph = graph;
+        let before = vec![self.selected_project.clone()];
+        let task = &mut self.active_tasks_list.array[val];
+        let description = format!("set the dependencies of {}", task.name);
+        task.depends_on = dependencies;
+        task.mark_updated();
+        self.save_change(description, before, vec![dependency_entry]);
+    }
+
+    fn show_unblocked_tasks(&mut self, completed_task_ids: &[Uuid]) {
+        self.dependency_graph.add_project(&self.selected_project);
+        let mut unblocked: Vec<String> = vec![];
+        for completed_task_id in completed_task_ids {
+            for id in self.dependency_graph.get_unblocked_by(completed_task_id) {
+                let reference = self
+                    .dependency_graph
+                    .get_reference(&id, self.project_name.as_str());
+                if !unblocked.contains(&reference) {
+                    unblocked.push(reference);
+                }
+            }
+        }
+        if !unblocked.is_empty() {
+            self.create_message_popup(format!("Unblocked: {}", unblocked.join(", ")));
+        }
+    }
+
+    fn get_description_text(&self, task: &Task) -> String {
+        let mut lines = vec![task.get_timestamps_description()];
+        if let Some(schedule) = task.get_schedule_description() {
+            lines.push(schedule);
+        }
+        let mut labels: Vec<String> = vec![];
+        if let Some(priority) = task.priority {
+            labels.push(format!("Priority: {}", priority));
+        }
+        if !task.tags.is_empty() {
+            labels.push(format!("Tags: {}", task.tags.join(", ")));
+        }
+        if !labels.is_empty() {
+            lines.push(labels.join(" | "));
+        }
+        let open_dependencies = self
+            .dependency_graph
+            .get_open_dependencies(&task.depends_on);
+        if !open_dependencies.is_empty() {
+            lines.push(format!(
+                "Blocked by: {}",
+                open_dependencies
+                    .iter()
+                    .map(|id| {
+                        self.dependency_graph
+                            .get_reference(id, self.project_name.as_str())
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+        lines.push(String::new());
+        lines.push(task.description.clone());
+        if let Some(progress) = task.get_checklist_progress() {
+            lines.push(String::new());
+            lines.push(format!("Checklist ({}):", progress));
+            for (index, item) in task.checklist.iter().enumerate() {
+                lines.push(format!(
+                    "{}[{}] {}",
+                    match index == self.checklist_cursor.min(task.checklist.len() - 1) {
+                        true => "> ",
+                        false => "  ",
+                    },
+                    match item.done {
+                        true => "x",
+                        false => " ",
+                    },
+                    item.text
+                ));
+            }
+        }
+        if !task.notes.is_empty() {
+            lines.push(String::new());
+            lines.push(format!("Notes ({}):", task.notes.len()));
+            lines.extend(task.notes.iter().map(|note| note.describe()));
+        }
+        lines.join("\n")
+    }
+
+    fn get_activity_text(&self) -> String {
+        match self.activity.is_empty() {
+            true => String::from("No activity recorded"),
+            false => self
+                .activity
+                .iter()
+                .rev()
+                .map(|entry| entry.describe())
+                .collect::<Vec<String>>()
+                .join("\n"),
+        }
+    }
+
+    /// Sorting only changes the order the lists are shown in, changes keep the manual order.
+    fn cycle_sort_order(&mut self) {
+        self.sort_order = self.sort_order.next();
+        self.update_project();
+    }
+
+    /// Puts the shown lists back in the project in the order they are stored in.
+    fn store_task_lists(&mut self) {
+        self.selected_project.active_tasks = get_stored_order(
+            &self.active_tasks_list.array,
+            &self.selected_project.active_tasks,
+        );
+        self.selected_project.completed_tasks = get_stored_order(
+            &self.completed_tasks_list.array,
+            &self.selected_project.completed_tasks,
+        );
     }
 
     fn create_message_popup(&mut self, message: String) {
         self.message_popup = PopupMessageWindow::new(message);
+        self.input_mode = InputMode::WriteMode;
     }
 
     fn update_project(&mut self) {
-        self.selected_project = match utils::load_project_from_path(self.working_path.clone()) {
+        self.selected_project = match self.storage.load_project(self.project_name.clone()) {
             Ok(updated_project) => updated_project,
             Err(e) => {
                 self.create_message_popup(e.to_string());
                 Project::default()
             }
         };
-        self.active_tasks_list = DisplayList::from(self.selected_project.active_tasks.clone());
-        self.completed_tasks_list =
-            DisplayList::from(self.selected_project.completed_tasks.clone());
+        let mut active_tasks = self.selected_project.active_tasks.clone();
+        let mut completed_tasks = self.selected_project.completed_tasks.clone();
+        self.sort_order.sort(&mut active_tasks);
+        self.sort_order.sort(&mut completed_tasks);
+        self.active_tasks_list = DisplayList::from(active_tasks);
+        self.completed_tasks_list = DisplayList::from(completed_tasks);
+        match self.storage.load_activity(self.project_name.clone()) {
+            Ok(activity) => self.activity = activity,
+            Err(e) => self.create_message_popup(e.to_string()),
+        }
+        self.dependency_graph.add_project(&self.selected_project);
+    }
+}
+
+/// The dependencies of every project, `current_project` replacing its stored version.
+fn load_dependency_graph(storage: &dyn Storage, current_project: &Project) -> DependencyGraph {
+    let mut graph = DependencyGraph::from_projects(&storage.load_projects().unwrap_or_default());
+    graph.add_project(current_project);
+    graph
+}
+
+/// The tasks of a sorted list in the order of the `stored` list, matched by id. Tasks the stored
+/// list does not have yet go last, in the order they are shown in.
+fn get_stored_order(list: &[Task], stored: &[Task]) -> Vec<Task> {
+    let mut tasks = list.to_vec();
+    tasks.sort_by_key(|task| {
+        stored
+            .iter()
+            .position(|stored_task| stored_task.id == task.id)
+            .unwrap_or(stored.len())
+    });
+    tasks
+}
+
+/// What a change did, as listed in the activity log when it is undone.
+fn describe_change(action: &str, tasks: &[Task]) -> String {
+    match tasks {
+        [task] => format!("{} {}", action, task.name),
+        _ => format!("{} {} tasks", action, tasks.len()),
+    }
+}
+
+/// The checklist as written in the input popup: `[x] Tests; [ ] Docs`, a semicolon of an item
+/// being written `\;`.
+fn format_checklist(checklist: &[ChecklistItem]) -> String {
+    checklist
+        .iter()
+        .map(|item| {
+            let check_box = match item.done {
+                true => "[x]",
+                false => "[ ]",
+            };
+            format!("{} {}", check_box, item.text.replace(';', "\\;"))
+        })
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
+/// Splits the input on the semicolons that are not escaped, unescaping the others.
+fn split_checklist_items(input: &str) -> Vec<String> {
+    let mut items: Vec<String> = vec![String::new()];
+    let mut characters = input.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '\\' if characters.peek() == Some(&';') => {
+                items.last_mut().unwrap().push(';');
+                characters.next();
+            }
+            ';' => items.push(String::new()),
+            _ => items.last_mut().unwrap().push(character),
+        }
+    }
+    items
+}
+
+/// Items without a check box are not done yet.
+fn parse_checklist(input: &str) -> Vec<ChecklistItem> {
+    split_checklist_items(input)
+        .iter()
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            let done = item.starts_with("[x]") || item.starts_with("[X]");
+            let text = match done || item.starts_with("[ ]") {
+                true => item[3..].trim(),
+                false => item,
+            };
+            ChecklistItem {
+                text: String::from(text),
+                done,
+            }
+        })
+        .filter(|item| !item.text.is_empty())
+        .collect()
+}
+
+/// Adds the tags of `input`, separated by commas, and removes those written `-tag`.
+fn edit_tags(tags: &[String], input: &str) -> Vec<String> {
+    let mut edited_tags = tags.to_vec();
+    for tag in input
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+    {
+        match tag.strip_prefix('-') {
+            Some(removed_tag) => edited_tags.retain(|t| t != removed_tag.trim()),
+            None => {
+                let added_tag = tag.trim_start_matches('+').trim().to_string();
+                if !added_tag.is_empty() && !edited_tags.contains(&added_tag) {
+                    edited_tags.push(added_tag);
+                }
+            }
+        }
+    }
+    edited_tags
+}
+
+fn describe_tags(tags: &[String]) -> String {
+    match tags.is_empty() {
+        true => String::from("tags: none"),
+        false => format!("tags: {}", tags.join(", ")),
     }
 }
 
+/// A todo.txt priority, an uppercase letter, or `None` for an empty input.
+fn parse_priority(input: &str) -> Result<Option<char>, String> {
+    let input = input.trim();
+    let mut characters = input.chars();
+    match (characters.next(), characters.next()) {
+        (None, _) => Ok(None),
+        _ if input.eq_ignore_ascii_case("none") => Ok(None),
+        (Some(letter), None) if letter.is_ascii_alphabetic() => {
+            Ok(Some(letter.to_ascii_uppercase()))
+        }
+        _ => Err(format!(
+            "Invalid priority {}, it is a letter from A to Z",
+            input
+        )),
+    }
+}
+
+fn parse_due_date(input: &str) -> Result<Option<NaiveDate>, String> {
+    let input = input.trim();
+    if input.is_empty() || input.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| format!("Invalid date {}, it is written YYYY-MM-DD", input))
+}
+
+fn edit_activity(task: &Task, new_description: String) -> ActivityEntry {
+    ActivityEntry::new(
+        ActivityKind::EditTask,
+        task.name.clone(),
+        Some(task.description.clone()),
+        Some(new_description),
+    )
+}
+
 impl Service for TaskService {
-    fn set_working_directory(&mut self, path: PathBuf) {
-        self.working_path = path;
+    fn set_storage(&mut self, storage: Box<dyn Storage>) {
+        self.storage = storage;
+        self.update_project();
     }
 }
 
 impl Drawable for TaskService {
-    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect) {
+    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect, theme: &Theme) {
         let initial_layout = Layout::default()
             .direction(Vertical)
             .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
@@ -161,42 +1374,75 @@ impl Drawable for TaskService {
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
             .split(initial_layout[0]);
 
-        let active_task_block = Block::default().borders(Borders::ALL).title("Active Tasks");
+        // Selected tasks are marked in the text so the selection shows without colors too
+        let get_mark = |task: &Task| match self.selected_ids.contains(&task.id) {
+            true => "* ",
+            false => "",
+        };
+        let get_progress = |task: &Task| match task.get_checklist_progress() {
+            Some(progress) => format!(" [{}]", progress),
+            None => String::new(),
+        };
+        let active_task_block = Block::default().borders(Borders::ALL).title(format!(
+            "Active Tasks{}{}",
+            self.sort_order.get_title_suffix(),
+            self.get_selection_title_suffix(&self.active_tasks_list.array)
+        ));
 
-        let active_task_display_list = List::new::<Vec<ListItem>>(
-            self.active_tasks_list
-                .array
-                .clone()
-                .into_iter()
-                .map(|task| ListItem::new(Text::from(utils::wrap(task.name, task_layout[0].width as u32))))
-                .collect(),
-        )
-        .block(active_task_block)
-        .highlight_style(
-            tui::style::Style::default()
-                .bg(tui::style::Color::Green)
-                .add_modifier(tui::style::Modifier::BOLD),
-        )
-        .highlight_symbol("-> ");
-
-        let completed_task_block = Block::default()
-            .borders(Borders::ALL)
-            .title("Completed Tasks");
-        let completed_task_display_list = List::new::<Vec<ListItem>>(
-            self.completed_tasks_list
-                .array
-                .clone()
-                .into_iter()
-                .map(|task| ListItem::new(Text::from(utils::wrap(task.name, task_layout[1].width as u32))))
-                .collect(),
-        )
-        .block(completed_task_block)
-        .highlight_style(
-            tui::style::Style::default()
-                .bg(tui::style::Color::Green)
-                .add_modifier(tui::style::Modifier::BOLD),
-        )
-        .highlight_symbol("-> ");
+        let active_task_items: Vec<ListItem> = self
+            .active_tasks_list
+            .array
+            .iter()
+            .map(
+                |task| match self.dependency_graph.is_blocked(&task.depends_on) {
+                    true => ListItem::new(Text::from(utils::wrap(
+                        format!(
+                            "{}[blocked] {}{}",
+                            get_mark(task),
+                            task.name,
+                            get_progress(task)
+                        ),
+                        task_layout[0].width as u32,
+                    )))
+                    .style(theme.muted_style()),
+                    false => ListItem::new(Text::from(utils::wrap(
+                        format!("{}{}{}", get_mark(task), task.name, get_progress(task)),
+                        task_layout[0].width as u32,
+                    ))),
+                },
+            )
+            .collect();
+        let active_task_heights: Vec<usize> =
+            active_task_items.iter().map(|item| item.height()).collect();
+        let active_task_display_list = List::new(active_task_items)
+            .block(active_task_block)
+            .highlight_style(theme.selection_style())
+            .highlight_symbol("-> ");
+
+        let completed_task_block = Block::default().borders(Borders::ALL).title(format!(
+            "Completed Tasks{}{}",
+            self.sort_order.get_title_suffix(),
+            self.get_selection_title_suffix(&self.completed_tasks_list.array)
+        ));
+        let completed_task_items: Vec<ListItem> = self
+            .completed_tasks_list
+            .array
+            .iter()
+            .map(|task| {
+                ListItem::new(Text::from(utils::wrap(
+                    format!("{}{}{}", get_mark(task), task.name, get_progress(task)),
+                    task_layout[1].width as u32,
+                )))
+            })
+            .collect();
+        let completed_task_heights: Vec<usize> = completed_task_items
+            .iter()
+            .map(|item| item.height())
+            .collect();
+        let completed_task_display_list = List::new(completed_task_items)
+            .block(completed_task_block)
+            .highlight_style(theme.selection_style())
+            .highlight_symbol("-> ");
         if self.focused_on_active {
             frame.render_stateful_widget(
                 active_task_display_list,
@@ -212,87 +1458,466 @@ impl Drawable for TaskService {
                 &mut self.completed_tasks_list.state.clone(),
             );
         }
+        // The list without focus is drawn without its selection
+        self.active_tasks_list.set_drawn(
+            task_layout[0],
+            &active_task_heights,
+            self.active_tasks_list
+                .state
+                .selected()
+                .filter(|_| self.focused_on_active),
+        );
+        self.completed_tasks_list.set_drawn(
+            task_layout[1],
+            &completed_task_heights,
+            self.completed_tasks_list
+                .state
+                .selected()
+                .filter(|_| !self.focused_on_active),
+        );
         // Lower layout
-        let description_block = Block::default().title("Description").borders(Borders::ALL);
-        let description: String = match self.focused_on_active {
-            true => match self.active_tasks_list.state.selected() {
-                Some(val) => self.active_tasks_list.array[val].description.clone(),
-                None => String::from("No task selected"),
-            },
-            false => match self.completed_tasks_list.state.selected() {
-                Some(val) => self.completed_tasks_list.array[val].description.clone(),
-                None => String::from("No task selected"),
-            },
+        let selected_task = self.get_selected_task();
+        let (lower_pane_title, lower_pane_text) = match (self.lower_pane, selected_task) {
+            (LowerPane::Activity, _) => ("Activity log", self.get_activity_text()),
+            (LowerPane::Description, Some(task)) => {
+                ("Description", self.get_description_text(task))
+            }
+            (LowerPane::Dependencies, Some(task)) => (
+                "Dependencies",
+                self.dependency_graph.describe_graph(&task.id),
+            ),
+            (LowerPane::Description, None) => ("Description", String::from("No task selected")),
+            (LowerPane::Dependencies, None) => ("Dependencies", String::from("No task selected")),
         };
-        let description_paragraph = Paragraph::new(Text::from(description))
-            .block(description_block)
-            .wrap(Wrap { trim: false });
-        frame.render_widget(description_paragraph, initial_layout[1]);
+        let lower_pane_block = Block::default()
+            .title(lower_pane_title)
+            .borders(Borders::ALL);
+        let lower_pane_paragraph = Paragraph::new(Text::from(lower_pane_text))
+            .block(lower_pane_block)
+            .wrap(Wrap { trim: false })
+            .scroll((self.lower_pane_scroll, 0));
+        frame.render_widget(lower_pane_paragraph, initial_layout[1]);
+        self.lower_pane_area.set(initial_layout[1]);
 
         // Popups
         if self.input_popup.is_active() {
-            self.input_popup.display(frame, layout);
+            self.input_popup.display(frame, layout, theme);
+        }
+        if self.chart_popup.is_active() {
+            self.chart_popup.display(frame, layout, theme);
+        }
+        if self.delete_tasks_popup.is_active() {
+            self.delete_tasks_popup.display(frame, layout, theme);
         }
         if self.message_popup.is_active() {
-            self.message_popup.display(frame, layout);
+            self.message_popup.display(frame, layout, theme);
         }
     }
 }
 
+impl MouseReceptor for TaskService {
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
+        let (column, row) = (mouse_event.column, mouse_event.row);
+        if let InputMode::WriteMode = self.input_mode {
+            if let MouseEventKind::Down(MouseButton::Left) = mouse_event.kind {
+                let keys = if self.message_popup.is_active() {
+                    self.message_popup.get_keys_for_click(column, row)
+                } else if self.delete_tasks_popup.is_active() {
+                    self.delete_tasks_popup.get_keys_for_click(column, row)
+                } else {
+                    vec![]
+                };
+                for key in keys {
+                    self.handle_input_key(key);
+                }
+            }
+            return;
+        }
+        let on_active_list = self.active_tasks_list.is_drawn_at(column, row);
+        let on_completed_list = self.completed_tasks_list.is_drawn_at(column, row);
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(index) = self.active_tasks_list.get_index_at(column, row) {
+                    self.focused_on_active = true;
+                    self.active_tasks_list.state.select(Some(index));
+                    self.checklist_cursor = 0;
+                } else if let Some(index) = self.completed_tasks_list.get_index_at(column, row) {
+                    self.focused_on_active = false;
+                    self.completed_tasks_list.state.select(Some(index));
+                    self.checklist_cursor = 0;
+                }
+            }
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown
+                if on_active_list || on_completed_list =>
+            {
+                self.focused_on_active = on_active_list;
+                match mouse_event.kind {
+                    MouseEventKind::ScrollUp => self.handle_input_key(KeyCode::Up),
+                    _ => self.handle_input_key(KeyCode::Down),
+                }
+            }
+            MouseEventKind::ScrollUp if ui::is_inside(self.lower_pane_area.get(), column, row) => {
+                self.lower_pane_scroll = self.lower_pane_scroll.saturating_sub(1);
+            }
+            MouseEventKind::ScrollDown
+                if ui::is_inside(self.lower_pane_area.get(), column, row) =>
+            {
+                self.lower_pane_scroll = self.lower_pane_scroll.saturating_add(1);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum TaskAction {
+    Previous,
+    Next,
+    FocusActive,
+    FocusCompleted,
+    Complete,
+    Uncomplete,
+    Add,
+    Rename,
+    EditDescription,
+    AddNote,
+    EditChecklist,
+    PreviousChecklistItem,
+    NextChecklistItem,
+    ToggleChecklistItem,
+    EditRecurrence,
+    EditDependencies,
+    ToggleDependencies,
+    CycleSortOrder,
+    ToggleActivity,
+    ScrollPaneUp,
+    ScrollPaneDown,
+    ShowCharts,
+    ToggleSelected,
+    ToggleRangeSelection,
+    ClearSelection,
+    Delete,
+    MoveTo,
+    EditTags,
+    EditPriority,
+    EditDueDate,
+    Undo,
+}
+
+static TASK_BINDINGS: &[KeyBinding<TaskAction>] = &[
+    KeyBinding::new(&[KeyCode::Up], "Up", "Previous task", TaskAction::Previous),
+    KeyBinding::new(&[KeyCode::Down], "Down", "Next task", TaskAction::Next),
+    KeyBinding::new(
+        &[KeyCode::Left],
+        "Left",
+        "Active tasks",
+        TaskAction::FocusActive,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Right],
+        "Right",
+        "Completed tasks",
+        TaskAction::FocusCompleted,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('c')],
+        "C",
+        "Mark as completed",
+        TaskAction::Complete,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('u')],
+        "U",
+        "Mark as incomplete",
+        TaskAction::Uncomplete,
+    ),
+    KeyBinding::new(&[KeyCode::Char('a')], "A", "Add task", TaskAction::Add),
+    KeyBinding::new(
+        &[KeyCode::Char('n')],
+        "N",
+        "Rename task",
+        TaskAction::Rename,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('e')],
+        "E",
+        "Edit task description",
+        TaskAction::EditDescription,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('w')],
+        "W",
+        "Write a note",
+        TaskAction::AddNote,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('k')],
+        "K",
+        "Edit checklist",
+        TaskAction::EditChecklist,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('[')],
+        "[",
+        "Previous checklist item",
+        TaskAction::PreviousChecklistItem,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char(']')],
+        "]",
+        "Next checklist item",
+        TaskAction::NextChecklistItem,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('x')],
+        "X",
+        "Check checklist item",
+        TaskAction::ToggleChecklistItem,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('r')],
+        "R",
+        "Repeat task",
+        TaskAction::EditRecurrence,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('p')],
+        "P",
+        "Set dependencies",
+        TaskAction::EditDependencies,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('g')],
+        "G",
+        "Dependency graph",
+        TaskAction::ToggleDependencies,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('o')],
+        "O",
+        "Sort by date",
+        TaskAction::CycleSortOrder,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('l')],
+        "L",
+        "Activity log",
+        TaskAction::ToggleActivity,
+    ),
+    KeyBinding::new(
+        &[KeyCode::PageUp],
+        "PgUp",
+        "Scroll pane up",
+        TaskAction::ScrollPaneUp,
+    ),
+    KeyBinding::new(
+        &[KeyCode::PageDown],
+        "PgDn",
+        "Scroll pane down",
+        TaskAction::ScrollPaneDown,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('b')],
+        "B",
+        "Burndown charts",
+        TaskAction::ShowCharts,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char(' ')],
+        "Space",
+        "Select task",
+        TaskAction::ToggleSelected,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('v')],
+        "V",
+        "Select a range",
+        TaskAction::ToggleRangeSelection,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Esc],
+        "Esc",
+        "Clear selection",
+        TaskAction::ClearSelection,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('d')],
+        "D",
+        "Delete tasks",
+        TaskAction::Delete,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('m')],
+        "M",
+        "Move to project",
+        TaskAction::MoveTo,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('t')],
+        "T",
+        "Edit tags",
+        TaskAction::EditTags,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('i')],
+        "I",
+        "Set priority",
+        TaskAction::EditPriority,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('D')],
+        "Shift-D",
+        "Set due date",
+        TaskAction::EditDueDate,
+    ),
+    KeyBinding::new(&[KeyCode::Char('z')], "Z", "Undo", TaskAction::Undo),
+];
+
 impl InputReceptor for TaskService {
     fn handle_input_key(&mut self, key_code: KeyCode) {
         match self.input_mode {
-            InputMode::CommandMode => match key_code {
-                KeyCode::Left => {
+            InputMode::CommandMode => match find_action(TASK_BINDINGS, key_code) {
+                Some(TaskAction::FocusActive) => {
                     self.focused_on_active = true;
+                    self.checklist_cursor = 0;
                 }
-                KeyCode::Right => {
+                Some(TaskAction::FocusCompleted) => {
                     self.focused_on_active = false;
+                    self.checklist_cursor = 0;
                 }
-                KeyCode::Char('a') => {
+                Some(TaskAction::Add) => {
                     self.add_task_command();
                 }
-                KeyCode::Char('c') => {
-                    if self.focused_on_active {
-                        self.mark_selected_task_as_completed();
-                        self.update_project();
-                    }
+                Some(TaskAction::Complete) => {
+                    self.complete_target_tasks();
                 }
-                KeyCode::Char('u') => {
-                    if !self.focused_on_active {
-                        self.mark_selected_task_as_uncompleted();
-                        self.update_project();
-                    }
+                Some(TaskAction::Uncomplete) => {
+                    self.reopen_target_tasks();
+                }
+                Some(TaskAction::ToggleSelected) => {
+                    self.toggle_selected_task();
+                }
+                Some(TaskAction::ToggleRangeSelection) => {
+                    self.toggle_range_selection();
+                }
+                Some(TaskAction::ClearSelection) => {
+                    self.clear_selection();
+                }
+                Some(TaskAction::Delete) => {
+                    self.confirm_delete_target_tasks();
+                }
+                Some(TaskAction::MoveTo) => {
+                    self.edit_target_tasks_command(MoveTo);
+                }
+                Some(TaskAction::EditTags) => {
+                    self.edit_target_tasks_command(EditTags);
+                }
+                Some(TaskAction::EditPriority) => {
+                    self.edit_target_tasks_command(EditPriority);
                 }
-                KeyCode::Char('e') => {
+                Some(TaskAction::EditDueDate) => {
+                    self.edit_target_tasks_command(EditDueDate);
+                }
+                Some(TaskAction::Undo) => {
+                    self.undo_last_change();
+                }
+                Some(TaskAction::Rename) => {
+                    self.rename_task_command();
+                }
+                Some(TaskAction::EditDescription) => {
                     self.edit_task_description();
                 }
-                KeyCode::Up => {
+                Some(TaskAction::AddNote) => {
+                    self.add_note_command();
+                }
+                Some(TaskAction::EditChecklist) => {
+                    self.edit_checklist_command();
+                }
+                Some(TaskAction::PreviousChecklistItem) => {
+                    self.move_checklist_cursor(false);
+                }
+                Some(TaskAction::NextChecklistItem) => {
+                    self.move_checklist_cursor(true);
+                }
+                Some(TaskAction::ToggleChecklistItem) => {
+                    self.toggle_checklist_item();
+                }
+                Some(TaskAction::ShowCharts) => {
+                    self.show_charts();
+                }
+                Some(TaskAction::CycleSortOrder) => {
+                    self.cycle_sort_order();
+                }
+                Some(TaskAction::EditRecurrence) => {
+                    self.edit_task_recurrence();
+                }
+                Some(TaskAction::ToggleActivity) => {
+                    self.toggle_lower_pane(LowerPane::Activity);
+                }
+                Some(TaskAction::ToggleDependencies) => {
+                    self.toggle_lower_pane(LowerPane::Dependencies);
+                }
+                Some(TaskAction::EditDependencies) => {
+                    self.edit_task_dependencies();
+                }
+                Some(TaskAction::ScrollPaneDown) => {
+                    self.lower_pane_scroll = self.lower_pane_scroll.saturating_add(5);
+                }
+                Some(TaskAction::ScrollPaneUp) => {
+                    self.lower_pane_scroll = self.lower_pane_scroll.saturating_sub(5);
+                }
+                Some(TaskAction::Previous) => {
                     if self.focused_on_active {
                         self.active_tasks_list.previous();
                     } else {
                         self.completed_tasks_list.previous();
                     }
+                    self.checklist_cursor = 0;
+                    self.extend_range_selection();
                 }
-                KeyCode::Down => {
+                Some(TaskAction::Next) => {
                     if self.focused_on_active {
                         self.active_tasks_list.next();
                     } else {
                         self.completed_tasks_list.next();
                     }
+                    self.checklist_cursor = 0;
+                    self.extend_range_selection();
                 }
-                _ => {}
+                None => {}
             },
             InputMode::WriteMode => {
                 match key_code {
                     _ => {
+                        if self.chart_popup.is_active() {
+                            self.chart_popup.handle_input_key(key_code);
+                            if self.chart_popup.is_completed() {
+                                self.chart_popup.set_active(false);
+                                self.input_mode = CommandMode;
+                            }
+                            return;
+                        }
                         if self.message_popup.is_active() {
                             self.message_popup.handle_input_key(key_code);
                             if self.message_popup.is_completed() {
                                 self.message_popup.set_active(false);
+                                if !self.input_popup.is_active() {
+                                    self.input_mode = CommandMode;
+                                }
                                 return;
                             }
                         }
+                        if self.delete_tasks_popup.is_active() {
+                            self.delete_tasks_popup.handle_input_key(key_code);
+                            if self.delete_tasks_popup.is_completed() {
+                                self.delete_tasks_popup.set_active(false);
+                                self.input_mode = CommandMode;
+                                if self.delete_tasks_popup.get_choice() {
+                                    self.delete_target_tasks();
+                                }
+                            }
+                            return;
+                        }
                         self.input_popup.handle_input_key(key_code);
                         if !self.input_popup.is_active() {
                             self.input_mode = CommandMode;
@@ -301,90 +1926,51 @@ impl InputReceptor for TaskService {
                         if self.input_popup.is_completed() {
                             match self.input_popup_type {
                                 AddName => {
-                                    self.selected_project.add_task(
+                                    self.add_task_named(self.input_popup.get_input_data());
+                                }
+                                EditDependencies => {
+                                    self.set_selected_task_dependencies(
+                                        self.input_popup.get_input_data(),
+                                    );
+                                }
+                                EditRecurrence => {
+                                    self.set_selected_task_recurrence(
+                                        self.input_popup.get_input_data(),
+                                    );
+                                }
+                                EditChecklist => {
+                                    self.set_selected_task_checklist(
+                                        self.input_popup.get_input_data(),
+                                    );
+                                }
+                                AddNote => {
+                                    self.add_note_to_selected_task(
+                                        self.input_popup.get_input_data(),
+                                    );
+                                }
+                                Rename => {
+                                    self.rename_selected_task(self.input_popup.get_input_data());
+                                }
+                                MoveTo => {
+                                    self.move_target_tasks(self.input_popup.get_input_data());
+                                }
+                                EditTags => {
+                                    self.set_target_tasks_tags(self.input_popup.get_input_data());
+                                }
+                                EditPriority => {
+                                    self.set_target_tasks_priority(
+                                        self.input_popup.get_input_data(),
+                                    );
+                                }
+                                EditDueDate => {
+                                    self.set_target_tasks_due_date(
                                         self.input_popup.get_input_data(),
-                                        String::from("Description"),
                                     );
-                                    match self
-                                        .selected_project
-                                        .write_project_full_path(self.working_path.clone())
-                                    {
-                                        Ok(_) => {
-                                            self.input_popup.set_active(false);
-                                            self.input_mode = CommandMode;
-                                            self.update_project();
-                                        }
-                                        Err(e) => {
-                                            self.create_message_popup(e.to_string());
-                                        }
-                                    };
                                 }
                                 EditDescription => {
-                                    let inputted_string = self.input_popup.get_input_data();
-                                    match self.focused_on_active {
-                                        true => {
-                                            match self.active_tasks_list.state.selected() {
-                                                Some(val) => {
-                                                    self.active_tasks_list.array[val].description =
-                                                        inputted_string;
-                                                    self.selected_project.active_tasks =
-                                                        self.active_tasks_list.array.clone();
-                                                    match self
-                                                        .selected_project
-                                                        .write_project_full_path(
-                                                            self.working_path.clone(),
-                                                        ) {
-                                                        Ok(_) => {
-                                                            self.input_popup.set_active(false);
-                                                            self.input_mode = CommandMode;
-                                                            self.update_project();
-                                                        }
-                                                        Err(e) => {
-                                                            self.create_message_popup(
-                                                                e.to_string(),
-                                                            );
-                                                        }
-                                                    }
-                                                }
-                                                None => {
-                                                    self.create_message_popup(String::from(
-                                                        "Selected task is invalid",
-                                                    ));
-                                                }
-                                            };
-                                        }
-                                        false => {
-                                            match self.completed_tasks_list.state.selected() {
-                                                Some(val) => {
-                                                    self.completed_tasks_list.array[val]
-                                                        .description = inputted_string;
-                                                    self.selected_project.completed_tasks =
-                                                        self.completed_tasks_list.array.clone();
-                                                    match self
-                                                        .selected_project
-                                                        .write_project_full_path(
-                                                            self.working_path.clone(),
-                                                        ) {
-                                                        Ok(_) => {
-                                                            self.input_popup.set_active(false);
-                                                            self.input_mode = CommandMode;
-                                                            self.update_project();
-                                                        }
-                                                        Err(e) => {
-                                                            self.create_message_popup(
-                                                                e.to_string(),
-                                                            );
-                                                        }
-                                                    }
-                                                }
-                                                None => {
-                                                    self.create_message_popup(String::from(
-                                                        "Selected task is invalid",
-                                                    ));
-                                                }
-                                            };
-                                        }
-                                    };
+                                    self.set_selected_task_description(
+                                        self.input_popup.get_input_data(),
+                                    );
                                 }
                             }
                         }
@@ -396,14 +1982,34 @@ impl InputReceptor for TaskService {
 
     fn get_controls_description(&self) -> String {
         if self.message_popup.is_active() {
-            return self.message_popup.get_controls_description();
+            self.message_popup.get_controls_description()
+        } else if self.delete_tasks_popup.is_active() {
+            self.delete_tasks_popup.get_controls_description()
+        } else if self.chart_popup.is_active() {
+            self.chart_popup.get_controls_description()
         } else if self.input_popup.is_active() {
-            return self.input_popup.get_controls_description();
+            self.input_popup.get_controls_description()
         } else {
-            String::from("Navigate with arrows | C: Mark as completed | U: Mark as incomplete | A: Add task | E: Edit task description | Tab: Back To Projects")
+            describe_controls(TASK_BINDINGS)
         }
     }
 
+    fn get_help_sections(&self) -> Vec<HelpSection> {
+        let mut sections = if self.message_popup.is_active() {
+            self.message_popup.get_help_sections()
+        } else if self.delete_tasks_popup.is_active() {
+            self.delete_tasks_popup.get_help_sections()
+        } else if self.chart_popup.is_active() {
+            self.chart_popup.get_help_sections()
+        } else if self.input_popup.is_active() {
+            self.input_popup.get_help_sections()
+        } else {
+            vec![]
+        };
+        sections.push(HelpSection::from_bindings("Tasks", TASK_BINDINGS));
+        sections
+    }
+
     fn get_input_mode(&self) -> InputMode {
         match self.input_mode {
             InputMode::CommandMode => InputMode::CommandMode,
@@ -411,3 +2017,166 @@ impl InputReceptor for TaskService {
         }
     }
 }
+
+#[test]
+fn bulk_edit_inputs() {
+    let tags = vec![String::from("work"), String::from("urgent")];
+    assert_eq!(
+        edit_tags(&tags, "home, -urgent, +work, "),
+        vec![String::from("work"), String::from("home")]
+    );
+    assert_eq!(parse_priority(" b"), Ok(Some('B')));
+    assert_eq!(parse_priority(""), Ok(None));
+    assert!(parse_priority("AB").is_err());
+    assert_eq!(
+        parse_due_date("2024-02-29"),
+        Ok(NaiveDate::from_ymd_opt(2024, 2, 29))
+    );
+    assert!(parse_due_date("tomorrow").is_err());
+    let tasks = vec![Task::new(String::from("Write"), String::new())];
+    assert_eq!(describe_change("delete", &tasks), "delete Write");
+}
+
+#[test]
+fn checklist_input() {
+    let checklist = parse_checklist("[x] Tests; [ ] Docs\\; API;; changelog ; [x]");
+    assert_eq!(
+        checklist,
+        vec![
+            ChecklistItem {
+                text: String::from("Tests"),
+                done: true,
+            },
+            ChecklistItem {
+                text: String::from("Docs; API"),
+                done: false,
+            },
+            ChecklistItem {
+                text: String::from("changelog"),
+                done: false,
+            },
+        ]
+    );
+    assert_eq!(
+        format_checklist(&checklist),
+        "[x] Tests; [ ] Docs\\; API; [ ] changelog"
+    );
+    assert_eq!(
+        parse_checklist(format_checklist(&checklist).as_str()),
+        checklist
+    );
+    let mut task = Task::new(String::from("Release"), String::new());
+    task.checklist = checklist;
+    assert_eq!(task.get_checklist_progress(), Some(String::from("1/3")));
+}
+
+#[test]
+fn sorting_keeps_the_stored_order() {
+    let mut tasks = vec![
+        Task::new(String::from("First"), String::new()),
+        Task::new(String::from("Second"), String::new()),
+        Task::new(String::from("Third"), String::new()),
+    ];
+    let now = chrono::Utc::now();
+    for (index, task) in tasks.iter_mut().enumerate() {
+        task.created_at = Some(now + chrono::Duration::minutes(index as i64));
+        task.updated_at = Some(now - chrono::Duration::minutes(index as i64));
+    }
+    tasks[1].completed_at = Some(now);
+    let names = |tasks: &[Task]| {
+        tasks
+            .iter()
+            .map(|t| t.name.clone())
+            .collect::<Vec<String>>()
+    };
+    let mut shown = tasks.clone();
+    TaskSortOrder::Created.sort(&mut shown);
+    assert_eq!(names(&shown), vec!["Third", "Second", "First"]);
+    TaskSortOrder::Updated.sort(&mut shown);
+    assert_eq!(names(&shown), vec!["First", "Second", "Third"]);
+    TaskSortOrder::Completed.sort(&mut shown);
+    assert_eq!(names(&shown), vec!["Second", "First", "Third"]);
+    TaskSortOrder::Manual.sort(&mut shown);
+    assert_eq!(names(&shown), vec!["Second", "First", "Third"]);
+
+    shown.push(Task::new(String::from("Added"), String::new()));
+    shown.remove(0);
+    assert_eq!(
+        names(&get_stored_order(&shown, &tasks)),
+        vec!["First", "Third", "Added"]
+    );
+}
+
+#[test]
+fn names_in_use_are_refused() {
+    use crate::storage::{create_test_folder, json_storage::JsonStorage};
+    use crate::structure::TaskContainer;
+    let folder = create_test_folder("rename");
+    let storage = JsonStorage::new(folder.clone());
+    let mut project = Project::new(String::from("Release"));
+    project.add_task(String::from("Tag"), String::new());
+    project.add_task(String::from("Publish"), String::new());
+    storage.save_project(&project).unwrap();
+    let mut service = TaskService::new(Box::new(storage), project.name.clone());
+
+    service.rename_selected_task(String::from(" Publish "));
+    assert!(service.message_popup.is_active());
+    assert_eq!(service.active_tasks_list.array[0].name, "Tag");
+
+    service.message_popup.set_active(false);
+    service.add_task_named(String::from("Publish "));
+    assert!(service.message_popup.is_active());
+    assert_eq!(service.active_tasks_list.array.len(), 2);
+
+    service.message_popup.set_active(false);
+    service.rename_selected_task(String::from("Tag v1.2"));
+    let saved = service.storage.load_project(project.name.clone()).unwrap();
+    assert_eq!(saved.active_tasks[0].name, "Tag v1.2");
+    assert_eq!(saved.active_tasks[0].id, project.active_tasks[0].id);
+    assert!(matches!(
+        service.activity.last().unwrap().kind,
+        ActivityKind::RenameTask
+    ));
+
+    service.undo_last_change();
+    let restored = service.storage.load_project(project.name.clone()).unwrap();
+    assert_eq!(restored.active_tasks[0].name, "Tag");
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn undo_keeps_later_changes() {
+    use crate::storage::{create_test_folder, json_storage::JsonStorage};
+    use crate::structure::TaskContainer;
+    let folder = create_test_folder("undo");
+    let storage = JsonStorage::new(folder.clone());
+    let mut project = Project::new(String::from("Release"));
+    project.add_task(String::from("Tag"), String::new());
+    storage.save_project(&project).unwrap();
+    let mut service = TaskService::new(Box::new(storage), project.name.clone());
+    let names = |tasks: &[Task]| {
+        tasks
+            .iter()
+            .map(|t| t.name.clone())
+            .collect::<Vec<String>>()
+    };
+
+    service.complete_target_tasks();
+    service.add_task_named(String::from("Publish"));
+    service.set_selected_task_description(String::from("On crates.io"));
+    service.undo_last_change();
+    let saved = service.storage.load_project(project.name.clone()).unwrap();
+    assert_eq!(names(&saved.active_tasks), vec!["Publish"]);
+    assert_eq!(saved.active_tasks[0].description, "Description");
+    assert_eq!(names(&saved.completed_tasks), vec!["Tag"]);
+
+    service.undo_last_change();
+    let saved = service.storage.load_project(project.name.clone()).unwrap();
+    assert!(saved.active_tasks.is_empty());
+    assert_eq!(names(&saved.completed_tasks), vec!["Tag"]);
+
+    service.undo_last_change();
+    let saved = service.storage.load_project(project.name.clone()).unwrap();
+    assert_eq!(names(&saved.active_tasks), vec!["Tag"]);
+    std::fs::remove_dir_all(folder).unwrap();
+}
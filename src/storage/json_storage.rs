@@ -0,0 +1,122 @@
+use crate::activity::ActivityEntry;
+use crate::storage::Storage;
+use crate::structure::Project;
+use crate::utils;
+use std::io::{Error, ErrorKind, Write};
+use std::path::PathBuf;
+
+pub static ACTIVITY_LOG_EXTENSION: &str = "log";
+
+/// Stores each project as a serialized `.pman` json file inside the working folder, or inside
+/// one of its group folders, next to a `.log` file holding its activity log as one json entry
+/// per line.
+pub struct JsonStorage {
+    working_folder: PathBuf,
+}
+
+impl JsonStorage {
+    pub fn new(working_folder: PathBuf) -> JsonStorage {
+        JsonStorage { working_folder }
+    }
+
+    /// The existing file of the project, wherever it is grouped, or a new one in the working
+    /// folder.
+    fn get_project_path(&self, project_name: String) -> PathBuf {
+        match utils::find_project_path(self.working_folder.as_path(), project_name.as_str()) {
+            Some(project_path) => project_path,
+            None => {
+                let mut project_path = self.working_folder.join(project_name);
+                project_path.set_extension(utils::PROJECT_FILE_EXTENSION);
+                project_path
+            }
+        }
+    }
+
+    fn get_activity_log_path(&self, project_name: String) -> PathBuf {
+        let mut log_path = self.get_project_path(project_name);
+        log_path.set_extension(ACTIVITY_LOG_EXTENSION);
+        log_path
+    }
+}
+
+impl Storage for JsonStorage {
+    fn load_projects(&self) -> Result<Vec<Project>, Error> {
+        Ok(utils::get_projects_in_path(self.working_folder.clone()))
+    }
+
+    fn load_project(&self, project_name: String) -> Result<Project, Error> {
+        utils::load_project_from_path(self.get_project_path(project_name))
+    }
+
+    fn save_project(&self, project: &Project) -> Result<(), Error> {
+        project.write_project_full_path(self.get_project_path(project.name.clone()))
+    }
+
+    /// Renames the files of the project, which stay in the same group folder. The log a deleted
+    /// project of the new name left gets the entries of the renamed one appended.
+    fn rename_project(&self, old_name: String, new_name: String) -> Result<(), Error> {
+        if old_name == new_name {
+            return Ok(());
+        }
+        let old_path = self.get_project_path(old_name.clone());
+        let mut new_path = old_path.with_file_name(new_name.as_str());
+        new_path.set_extension(utils::PROJECT_FILE_EXTENSION);
+        if new_path.exists()
+            || utils::find_project_path(self.working_folder.as_path(), new_name.as_str()).is_some()
+        {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("There already is a project named {}", new_name),
+            ));
+        }
+        let old_log_path = self.get_activity_log_path(old_name);
+        let new_log_path = new_path.with_extension(ACTIVITY_LOG_EXTENSION);
+        std::fs::rename(old_path, new_path)?;
+        if !old_log_path.exists() {
+            return Ok(());
+        }
+        match new_log_path.exists() {
+            true => {
+                let mut log_file = std::fs::OpenOptions::new()
+                    .append(true)
+                    .open(new_log_path)?;
+                log_file.write_all(std::fs::read_to_string(old_log_path.as_path())?.as_bytes())?;
+                std::fs::remove_file(old_log_path)
+            }
+            false => std::fs::rename(old_log_path, new_log_path),
+        }
+    }
+
+    fn delete_project(&self, project_name: String) -> Result<(), Error> {
+        utils::delete_project_of_name(project_name, self.working_folder.clone())
+    }
+
+    fn append_activity(&self, project_name: String, entry: &ActivityEntry) -> Result<(), Error> {
+        let mut log_file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.get_activity_log_path(project_name))?;
+        writeln!(log_file, "{}", serde_json::to_string(entry)?)
+    }
+
+    fn load_activity(&self, project_name: String) -> Result<Vec<ActivityEntry>, Error> {
+        let content = match std::fs::read_to_string(self.get_activity_log_path(project_name)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(Error::from))
+            .collect()
+    }
+
+    fn get_location(&self) -> PathBuf {
+        self.working_folder.clone()
+    }
+
+    fn get_project_location(&self, project_name: String) -> String {
+        String::from(self.get_project_path(project_name).to_str().unwrap())
+    }
+}
@@ -0,0 +1,341 @@
+pub mod json_storage;
+pub mod sqlite_storage;
+
+use crate::activity::ActivityEntry;
+use crate::structure::Project;
+use crate::utils;
+use json_storage::JsonStorage;
+use sqlite_storage::SqliteStorage;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+pub static SQLITE_FILE_NAME: &str = "projects.sqlite";
+/// Folder the json files are moved to once migrated, hidden so it is not read as a group.
+static JSON_BACKUP_FOLDER_NAME: &str = ".json-backup";
+
+pub enum StorageKind {
+    Json,
+    Sqlite,
+}
+
+/// Persistence backend for the projects of a working folder.
+pub trait Storage {
+    fn load_projects(&self) -> Result<Vec<Project>, Error>;
+    fn load_project(&self, project_name: String) -> Result<Project, Error>;
+    fn save_project(&self, project: &Project) -> Result<(), Error>;
+    /// Fails with `ErrorKind::AlreadyExists` when a project of the new name exists.
+    fn rename_project(&self, old_name: String, new_name: String) -> Result<(), Error>;
+    /// Removes the project, leaving its activity log so `project_manager log` still shows the
+    /// history of the project. A project given the same name later continues that log, after
+    /// the entry of the deletion.
+    fn delete_project(&self, project_name: String) -> Result<(), Error>;
+    /// Adds an entry at the end of the activity log of the project, entries are never modified.
+    fn append_activity(&self, project_name: String, entry: &ActivityEntry) -> Result<(), Error>;
+    /// The activity log of the project, oldest entry first.
+    fn load_activity(&self, project_name: String) -> Result<Vec<ActivityEntry>, Error>;
+    fn get_location(&self) -> PathBuf;
+    fn get_project_location(&self, project_name: String) -> String;
+}
+
+pub fn get_storage_kind(working_folder: &Path) -> StorageKind {
+    match working_folder.join(SQLITE_FILE_NAME).exists() {
+        true => StorageKind::Sqlite,
+        false => StorageKind::Json,
+    }
+}
+
+/// Opens the backend used by the working folder: the SQLite database when
+/// one exists in it, the `.pman` json files otherwise.
+pub fn open_storage(working_folder: PathBuf) -> Result<Box<dyn Storage>, Error> {
+    let kind = get_storage_kind(&working_folder);
+    open_storage_of_kind(working_folder, kind)
+}
+
+pub fn open_storage_of_kind(
+    working_folder: PathBuf,
+    kind: StorageKind,
+) -> Result<Box<dyn Storage>, Error> {
+    match kind {
+        StorageKind::Json => Ok(Box::new(JsonStorage::new(working_folder))),
+        StorageKind::Sqlite => Ok(Box::new(SqliteStorage::open(
+            working_folder.join(SQLITE_FILE_NAME),
+        )?)),
+    }
+}
+
+/// Copies every project of `source` into `destination`, returning the number of projects copied.
+///
+/// Activity entries are appended after the last entry `destination` already has, so migrating
+/// back and forth does not duplicate them. Projects grouped by their folder get the group as
+/// their parent, since the copies are not in that folder.
+pub fn copy_projects(source: &dyn Storage, destination: &dyn Storage) -> Result<usize, Error> {
+    let projects = source.load_projects()?;
+    for project in projects.iter() {
+        let mut copy = project.clone();
+        if copy.parent.is_none() {
+            copy.parent = copy.folder_group.take();
+        }
+        destination.save_project(&copy)?;
+        let last_copied = destination
+            .load_activity(project.name.clone())?
+            .last()
+            .map(|entry| entry.at);
+        for entry in source.load_activity(project.name.clone())? {
+            if last_copied.map(|at| entry.at > at) != Some(false) {
+                destination.append_activity(project.name.clone(), &entry)?;
+            }
+        }
+    }
+    Ok(projects.len())
+}
+
+/// `path`, or `path` followed by the first number that names no existing file, so that
+/// backups never overwrite each other.
+fn get_unused_path(path: PathBuf) -> PathBuf {
+    let mut unused_path = path.clone();
+    let mut number = 1;
+    while unused_path.exists() {
+        let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(format!(".{}", number));
+        unused_path = path.with_file_name(file_name);
+        number += 1;
+    }
+    unused_path
+}
+
+/// Moves the project and activity log files of `folder` and of its group folders to the same
+/// place under `backup_folder`, removing the group folders left empty.
+fn move_json_files(folder: &Path, backup_folder: &Path) -> Result<(), Error> {
+    for entry in std::fs::read_dir(folder)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name();
+        if entry.file_type()?.is_dir() {
+            if !file_name.to_string_lossy().starts_with('.') {
+                move_json_files(path.as_path(), backup_folder.join(&file_name).as_path())?;
+                if std::fs::read_dir(path.as_path())?.next().is_none() {
+                    std::fs::remove_dir(path.as_path())?;
+                }
+            }
+            continue;
+        }
+        let is_json_file = path.extension().is_some_and(|extension| {
+            extension == utils::PROJECT_FILE_EXTENSION
+                || extension == json_storage::ACTIVITY_LOG_EXTENSION
+        });
+        if is_json_file {
+            std::fs::create_dir_all(backup_folder)?;
+            std::fs::rename(path.as_path(), backup_folder.join(&file_name))?;
+        }
+    }
+    Ok(())
+}
+
+fn refuse_overwrite(destination: &dyn Storage, force: bool) -> Result<(), Error> {
+    let count = destination.load_projects()?.len();
+    match count > 0 && !force {
+        true => Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!(
+                "{} already holds {} project(s), use --force to overwrite them",
+                destination.get_location().to_str().unwrap(),
+                count
+            ),
+        )),
+        false => Ok(()),
+    }
+}
+
+/// Converts the working folder to the requested backend.
+///
+/// The source is moved out of the way once copied so that it cannot be migrated again over
+/// newer changes: the json files go to a `.json-backup` folder, the database is renamed to
+/// `projects.sqlite.bak`. Both are numbered rather than replacing an earlier backup. A
+/// destination that already holds projects is only written over with `force`.
+pub fn migrate(working_folder: PathBuf, to: StorageKind, force: bool) -> Result<String, Error> {
+    match to {
+        StorageKind::Sqlite => {
+            let source = JsonStorage::new(working_folder.clone());
+            let destination = SqliteStorage::open(working_folder.join(SQLITE_FILE_NAME))?;
+            refuse_overwrite(&destination, force)?;
+            let count = copy_projects(&source, &destination)?;
+            let backup_folder = get_unused_path(working_folder.join(JSON_BACKUP_FOLDER_NAME));
+            move_json_files(working_folder.as_path(), backup_folder.as_path())?;
+            Ok(format!(
+                "Migrated {} project(s) to {}, json files moved to {}",
+                count,
+                destination.get_location().to_str().unwrap(),
+                backup_folder.to_str().unwrap()
+            ))
+        }
+        StorageKind::Json => {
+            let database_path = working_folder.join(SQLITE_FILE_NAME);
+            if !database_path.exists() {
+                return Ok(String::from("Working folder already uses json files"));
+            }
+            let count = {
+                let source = SqliteStorage::open(database_path.clone())?;
+                let destination = JsonStorage::new(working_folder.clone());
+                refuse_overwrite(&destination, force)?;
+                copy_projects(&source, &destination)?
+            };
+            let backup_path = get_unused_path(database_path.with_extension("sqlite.bak"));
+            std::fs::rename(database_path, backup_path.clone())?;
+            Ok(format!(
+                "Migrated {} project(s) to json files, database kept as {}",
+                count,
+                backup_path.to_str().unwrap()
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn create_test_folder(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("pman-{}-{}", name, uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(folder.as_path()).unwrap();
+    folder
+}
+
+#[cfg(test)]
+fn check_storage(storage: &dyn Storage) {
+    use crate::activity::ActivityKind;
+    use crate::structure::{Task, TaskContainer};
+    let mut project = Project::new(String::from("Release"));
+    project.add_task(String::from("Tag"), String::from("On main"));
+    project
+        .completed_tasks
+        .push(Task::new(String::from("Changelog"), String::new()));
+    storage.save_project(&project).unwrap();
+    let entry = ActivityEntry::new(ActivityKind::AddTask, String::from("Tag"), None, None);
+    storage
+        .append_activity(project.name.clone(), &entry)
+        .unwrap();
+
+    let loaded = storage.load_project(project.name.clone()).unwrap();
+    assert_eq!(loaded.active_tasks[0].name, "Tag");
+    assert_eq!(loaded.active_tasks[0].id, project.active_tasks[0].id);
+    assert_eq!(loaded.completed_tasks[0].name, "Changelog");
+    assert_eq!(storage.load_projects().unwrap().len(), 1);
+
+    storage
+        .rename_project(project.name.clone(), String::from("Launch"))
+        .unwrap();
+    assert!(storage.load_project(project.name.clone()).is_err());
+    assert_eq!(
+        storage.load_activity(String::from("Launch")).unwrap().len(),
+        1
+    );
+    storage
+        .save_project(&Project::new(String::from("Home")))
+        .unwrap();
+    storage
+        .append_activity(String::from("Home"), &entry)
+        .unwrap();
+    let error = storage
+        .rename_project(String::from("Home"), String::from("Launch"))
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::AlreadyExists);
+    assert_eq!(
+        storage
+            .load_project(String::from("Launch"))
+            .unwrap()
+            .active_tasks
+            .len(),
+        1
+    );
+    assert_eq!(
+        storage.load_activity(String::from("Home")).unwrap().len(),
+        1
+    );
+
+    let entry = ActivityEntry::new(
+        ActivityKind::DeleteProject,
+        String::from("Launch"),
+        None,
+        None,
+    );
+    storage
+        .append_activity(String::from("Launch"), &entry)
+        .unwrap();
+    storage.delete_project(String::from("Launch")).unwrap();
+    assert!(storage.load_project(String::from("Launch")).is_err());
+    assert_eq!(
+        storage.load_activity(String::from("Launch")).unwrap().len(),
+        2
+    );
+    storage
+        .rename_project(String::from("Home"), String::from("Launch"))
+        .unwrap();
+    assert_eq!(
+        storage.load_activity(String::from("Launch")).unwrap().len(),
+        3
+    );
+    assert_eq!(storage.load_projects().unwrap().len(), 1);
+}
+
+#[test]
+fn json_storage_round_trip() {
+    let folder = create_test_folder("json");
+    check_storage(&JsonStorage::new(folder.clone()));
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn sqlite_storage_round_trip() {
+    let folder = create_test_folder("sqlite");
+    check_storage(&SqliteStorage::open(folder.join(SQLITE_FILE_NAME)).unwrap());
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn folder_groups_are_not_saved() {
+    let folder = create_test_folder("groups");
+    std::fs::create_dir(folder.join("Work")).unwrap();
+    Project::new(String::from("Website"))
+        .write_project_full_path(folder.join("Work").join("Website.pman"))
+        .unwrap();
+    let json_storage = JsonStorage::new(folder.clone());
+    let project = json_storage.load_projects().unwrap().remove(0);
+    assert_eq!(project.folder_group.as_deref(), Some("Work"));
+    assert_eq!(project.parent, None);
+
+    json_storage.save_project(&project).unwrap();
+    let saved = utils::load_project_from_path(folder.join("Work").join("Website.pman")).unwrap();
+    assert_eq!(saved.parent, None);
+    migrate(folder.clone(), StorageKind::Sqlite, false).unwrap();
+    let sqlite_storage = SqliteStorage::open(folder.join(SQLITE_FILE_NAME)).unwrap();
+    let migrated = sqlite_storage.load_project(project.name).unwrap();
+    assert_eq!(migrated.parent.as_deref(), Some("Work"));
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn migrate_moves_the_source_aside() {
+    let folder = create_test_folder("migrate");
+    let json_storage = JsonStorage::new(folder.clone());
+    json_storage
+        .save_project(&Project::new(String::from("Home")))
+        .unwrap();
+
+    migrate(folder.clone(), StorageKind::Sqlite, false).unwrap();
+    assert!(json_storage.load_projects().unwrap().is_empty());
+    assert!(folder
+        .join(JSON_BACKUP_FOLDER_NAME)
+        .join("Home.pman")
+        .is_file());
+    // Json files written since would overwrite the newer database
+    json_storage
+        .save_project(&Project::new(String::from("Stale")))
+        .unwrap();
+    assert!(migrate(folder.clone(), StorageKind::Sqlite, false).is_err());
+    std::fs::remove_file(folder.join("Stale.pman")).unwrap();
+
+    migrate(folder.clone(), StorageKind::Json, false).unwrap();
+    assert_eq!(json_storage.load_projects().unwrap()[0].name, "Home");
+    assert!(folder.join("projects.sqlite.bak").is_file());
+    migrate(folder.clone(), StorageKind::Sqlite, false).unwrap();
+    assert!(migrate(folder.clone(), StorageKind::Json, false).is_ok());
+    assert!(folder.join("projects.sqlite.bak.1").is_file());
+    std::fs::remove_dir_all(folder).unwrap();
+}
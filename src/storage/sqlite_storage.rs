@@ -0,0 +1,307 @@
+use crate::activity::ActivityEntry;
+use crate::storage::Storage;
+use crate::structure::{Project, Task};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection};
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS projects (
+        name TEXT PRIMARY KEY,
+        description TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS tasks (
+        project TEXT NOT NULL,
+        completed INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS tasks_by_project ON tasks (project);
+    CREATE TABLE IF NOT EXISTS project_history (
+        project TEXT NOT NULL,
+        saved_at INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS activity_log (
+        project TEXT NOT NULL,
+        at TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS activity_log_by_project ON activity_log (project);
+";
+
+/// Stores every project of the working folder in a single SQLite database.
+///
+/// Projects and tasks live in their own tables so they can be queried across projects,
+/// every save appends the full project to `project_history` and `activity_log` holds the
+/// activity entries of every project.
+pub struct SqliteStorage {
+    connection: Connection,
+    database_path: PathBuf,
+}
+
+fn to_io_error(e: rusqlite::Error) -> Error {
+    Error::other(e)
+}
+
+impl SqliteStorage {
+    pub fn open(database_path: PathBuf) -> Result<SqliteStorage, Error> {
+        let connection = Connection::open(database_path.as_path()).map_err(to_io_error)?;
+        connection.execute_batch(SCHEMA).map_err(to_io_error)?;
+        Ok(SqliteStorage {
+            connection,
+            database_path,
+        })
+    }
+
+    fn load_tasks(&self, project: &mut Project) -> Result<(), Error> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT completed, data FROM tasks WHERE project = ?1 ORDER BY completed, position",
+            )
+            .map_err(to_io_error)?;
+        let rows = statement
+            .query_map(params![project.name], |row| {
+                Ok((row.get::<_, bool>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(to_io_error)?;
+        for row in rows {
+            let (completed, data) = row.map_err(to_io_error)?;
+            let task: Task = serde_json::from_str(data.as_str())?;
+            match completed {
+                true => project.completed_tasks.push(task),
+                false => project.active_tasks.push(task),
+            }
+        }
+        Ok(())
+    }
+
+    /// When the project was last saved, according to its history.
+    fn get_last_save_time(&self, project_name: &str) -> Result<DateTime<Utc>, Error> {
+        let saved_at: Option<i64> = self
+            .connection
+            .query_row(
+                "SELECT MAX(saved_at) FROM project_history WHERE project = ?1",
+                params![project_name],
+                |row| row.get(0),
+            )
+            .map_err(to_io_error)?;
+        Ok(saved_at
+            .and_then(|s| Utc.timestamp_opt(s, 0).single())
+            .unwrap_or_else(Utc::now))
+    }
+
+    fn project_from_row(&self, name: String, data: String) -> Result<Project, Error> {
+        let mut project: Project = serde_json::from_str(data.as_str())?;
+        project.name = name;
+        self.load_tasks(&mut project)?;
+        project.fill_missing_timestamps(self.get_last_save_time(project.name.as_str())?);
+        Ok(project)
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load_projects(&self) -> Result<Vec<Project>, Error> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT name, data FROM projects ORDER BY name")
+            .map_err(to_io_error)?;
+        let rows = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(to_io_error)?;
+        let mut projects = vec![];
+        for row in rows {
+            let (name, data) = row.map_err(to_io_error)?;
+            projects.push(self.project_from_row(name, data)?);
+        }
+        Ok(projects)
+    }
+
+    fn load_project(&self, project_name: String) -> Result<Project, Error> {
+        let data: String = match self.connection.query_row(
+            "SELECT data FROM projects WHERE name = ?1",
+            params![project_name],
+            |row| row.get(0),
+        ) {
+            Ok(data) => data,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("No project named {}", project_name),
+                ))
+            }
+            Err(e) => return Err(to_io_error(e)),
+        };
+        self.project_from_row(project_name, data)
+    }
+
+    fn save_project(&self, project: &Project) -> Result<(), Error> {
+        let mut project_header = project.clone();
+        project_header.active_tasks = vec![];
+        project_header.completed_tasks = vec![];
+        let saved_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        let transaction = self
+            .connection
+            .unchecked_transaction()
+            .map_err(to_io_error)?;
+        transaction
+            .execute(
+                "INSERT OR REPLACE INTO projects (name, description, data) VALUES (?1, ?2, ?3)",
+                params![
+                    project.name,
+                    project.description,
+                    serde_json::to_string(&project_header)?
+                ],
+            )
+            .map_err(to_io_error)?;
+        transaction
+            .execute(
+                "DELETE FROM tasks WHERE project = ?1",
+                params![project.name],
+            )
+            .map_err(to_io_error)?;
+        let task_lists = [
+            (false, &project.active_tasks),
+            (true, &project.completed_tasks),
+        ];
+        for (completed, tasks) in task_lists.iter() {
+            for (position, task) in tasks.iter().enumerate() {
+                transaction
+                    .execute(
+                        "INSERT INTO tasks (project, completed, position, name, description, data)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        params![
+                            project.name,
+                            completed,
+                            position as i64,
+                            task.name,
+                            task.description,
+                            serde_json::to_string(task)?
+                        ],
+                    )
+                    .map_err(to_io_error)?;
+            }
+        }
+        transaction
+            .execute(
+                "INSERT INTO project_history (project, saved_at, data) VALUES (?1, ?2, ?3)",
+                params![project.name, saved_at, serde_json::to_string(project)?],
+            )
+            .map_err(to_io_error)?;
+        transaction.commit().map_err(to_io_error)
+    }
+
+    fn rename_project(&self, old_name: String, new_name: String) -> Result<(), Error> {
+        if old_name == new_name {
+            return Ok(());
+        }
+        let transaction = self
+            .connection
+            .unchecked_transaction()
+            .map_err(to_io_error)?;
+        let name_taken: bool = transaction
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM projects WHERE name = ?1)",
+                params![new_name],
+                |row| row.get(0),
+            )
+            .map_err(to_io_error)?;
+        if name_taken {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("There already is a project named {}", new_name),
+            ));
+        }
+        let renamed = transaction
+            .execute(
+                "UPDATE projects SET name = ?1 WHERE name = ?2",
+                params![new_name, old_name],
+            )
+            .map_err(to_io_error)?;
+        if renamed == 0 {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("No project named {}", old_name),
+            ));
+        }
+        for statement in [
+            "UPDATE tasks SET project = ?1 WHERE project = ?2",
+            "UPDATE project_history SET project = ?1 WHERE project = ?2",
+            "UPDATE activity_log SET project = ?1 WHERE project = ?2",
+        ]
+        .iter()
+        {
+            transaction
+                .execute(statement, params![new_name, old_name])
+                .map_err(to_io_error)?;
+        }
+        transaction.commit().map_err(to_io_error)
+    }
+
+    fn delete_project(&self, project_name: String) -> Result<(), Error> {
+        let transaction = self
+            .connection
+            .unchecked_transaction()
+            .map_err(to_io_error)?;
+        transaction
+            .execute(
+                "DELETE FROM tasks WHERE project = ?1",
+                params![project_name],
+            )
+            .map_err(to_io_error)?;
+        transaction
+            .execute(
+                "DELETE FROM projects WHERE name = ?1",
+                params![project_name],
+            )
+            .map_err(to_io_error)?;
+        transaction.commit().map_err(to_io_error)
+    }
+
+    fn append_activity(&self, project_name: String, entry: &ActivityEntry) -> Result<(), Error> {
+        self.connection
+            .execute(
+                "INSERT INTO activity_log (project, at, data) VALUES (?1, ?2, ?3)",
+                params![
+                    project_name,
+                    entry.at.to_rfc3339(),
+                    serde_json::to_string(entry)?
+                ],
+            )
+            .map_err(to_io_error)?;
+        Ok(())
+    }
+
+    fn load_activity(&self, project_name: String) -> Result<Vec<ActivityEntry>, Error> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT data FROM activity_log WHERE project = ?1 ORDER BY rowid")
+            .map_err(to_io_error)?;
+        let rows = statement
+            .query_map(params![project_name], |row| row.get::<_, String>(0))
+            .map_err(to_io_error)?;
+        let mut entries = vec![];
+        for row in rows {
+            entries.push(serde_json::from_str(row.map_err(to_io_error)?.as_str())?);
+        }
+        Ok(entries)
+    }
+
+    fn get_location(&self) -> PathBuf {
+        self.database_path.clone()
+    }
+
+    fn get_project_location(&self, project_name: String) -> String {
+        format!("{}:{}", self.database_path.to_str().unwrap(), project_name)
+    }
+}
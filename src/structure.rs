@@ -1,5 +1,7 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use serde::Deserialize;
 use serde::Serialize;
+use uuid::Uuid;
 
 use std::io;
 use tui::backend::CrosstermBackend;
@@ -9,105 +11,160 @@ use tui::layout::{Constraint, Direction, Layout};
 use tui::text::Text;
 use tui::widgets::Paragraph;
 
-use crate::ui::{Drawable, InputMode, InputReceptor};
-use crate::utils;
+use crate::activity;
+use crate::recurrence::Recurrence;
+use crate::storage;
+use crate::storage::Storage;
+use crate::ui::{
+    describe_controls, find_action, Drawable, HelpEntry, HelpSection, InputMode, InputReceptor,
+    KeyBinding, MouseReceptor, PopupCommandPalette, PopupHelpWindow,
+};
+// Named as ui::Completable would clash with the trait of the same name below.
+use crate::ui::Completable as _;
 
+use crate::services::dashboard_service::DashboardService;
 use crate::services::project_service::ProjectManagementService;
 use crate::services::task_service::TaskService;
-use crossterm::event::KeyCode;
-use std::ops::Add;
-use std::path::PathBuf;
+use crate::terminal;
+use crate::theme::Theme;
+use crossterm::event::{KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use std::path::{Path, PathBuf};
 
 enum SelectedWindow {
     Project,
     Task,
+    Dashboard,
 }
 
+#[derive(Clone, Copy)]
+enum WindowAction {
+    Help,
+    Quit,
+    OpenTasks,
+    OpenStatistics,
+    BackToProjects,
+}
+
+const HELP_BINDING: KeyBinding<WindowAction> = KeyBinding::new(
+    &[KeyCode::Char('?'), KeyCode::F(1)],
+    "?, F1",
+    "Help",
+    WindowAction::Help,
+);
+/// Ctrl-P is read with its modifier in the event loop, it has no binding of its own.
+const COMMAND_PALETTE_LABEL: &str = "Ctrl-P";
+const COMMAND_PALETTE_DESCRIPTION: &str = "Command palette";
+const QUIT_BINDING: KeyBinding<WindowAction> =
+    KeyBinding::new(&[KeyCode::Char('q')], "Q", "Quit", WindowAction::Quit);
+
+static PROJECT_WINDOW_BINDINGS: &[KeyBinding<WindowAction>] = &[
+    HELP_BINDING,
+    QUIT_BINDING,
+    KeyBinding::new(
+        &[KeyCode::Tab, KeyCode::Enter],
+        "Tab, Enter",
+        "Go to Tasks",
+        WindowAction::OpenTasks,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Char('s')],
+        "S",
+        "Statistics",
+        WindowAction::OpenStatistics,
+    ),
+];
+
+static TASK_WINDOW_BINDINGS: &[KeyBinding<WindowAction>] = &[
+    HELP_BINDING,
+    QUIT_BINDING,
+    KeyBinding::new(
+        &[KeyCode::Tab],
+        "Tab",
+        "Back To Projects",
+        WindowAction::BackToProjects,
+    ),
+];
+
+static DASHBOARD_WINDOW_BINDINGS: &[KeyBinding<WindowAction>] = &[
+    HELP_BINDING,
+    QUIT_BINDING,
+    KeyBinding::new(
+        &[KeyCode::Tab, KeyCode::Esc, KeyCode::Char('s')],
+        "S, Tab",
+        "Back To Projects",
+        WindowAction::BackToProjects,
+    ),
+];
+
 pub struct Application<'a> {
     terminal: tui::Terminal<CrosstermBackend<io::Stdout>>,
     active_folder_path: std::path::PathBuf,
     project_window: ProjectManagementService<'a>,
     task_window: TaskService,
+    dashboard_window: DashboardService,
     pub is_running: bool,
     selected_window: SelectedWindow,
+    theme: Theme,
+    help_popup: PopupHelpWindow,
+    command_palette: PopupCommandPalette,
 }
 
 impl<'a> Application<'a> {
-    pub fn new(path: std::path::PathBuf) -> Application<'a> {
+    pub fn new(path: std::path::PathBuf, theme: Theme) -> Application<'a> {
+        terminal::setup_terminal().unwrap();
         let stdout = io::stdout();
         let backend = CrosstermBackend::new(stdout);
         let mut b_terminal = Terminal::new(backend).unwrap();
         b_terminal.clear().unwrap();
-        let app_project_window = ProjectManagementService::new(path.clone());
+        let app_project_window = ProjectManagementService::new(open_storage(&path));
+        let app_task_window = TaskService::new(open_storage(&path), String::new());
+        let app_dashboard_window = DashboardService::new(open_storage(&path));
         Application {
             terminal: b_terminal,
             active_folder_path: path,
             project_window: app_project_window,
-            task_window: TaskService::default(),
+            task_window: app_task_window,
+            dashboard_window: app_dashboard_window,
             is_running: true,
             selected_window: SelectedWindow::Project,
+            theme,
+            help_popup: PopupHelpWindow::default(),
+            command_palette: PopupCommandPalette::default(),
         }
     }
-    fn display_main_window(&mut self) {
-        let text_active_path = Text::from(self.active_folder_path.to_str().unwrap());
-        let project_window_ref = &mut self.project_window;
-        self.terminal
-            .draw(|f| {
-                let window_layout = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints(
-                        [
-                            Constraint::Percentage(5),
-                            Constraint::Percentage(90),
-                            Constraint::Percentage(5),
-                        ]
-                        .as_ref(),
-                    )
-                    .split(f.size());
-                let current_project_path = Paragraph::new(text_active_path);
-                f.render_widget(current_project_path, window_layout[0]);
-                let controls_string =
-                    String::from(project_window_ref.get_controls_description().as_str());
-                let controls_para = Paragraph::new(Text::from(controls_string));
-                f.render_widget(controls_para, window_layout[2]);
-                project_window_ref.display(f, window_layout[1]);
-            })
-            .unwrap();
-    }
-
-    fn display_tasks_window(&mut self) {
-        let project_name = self
-            .project_window
-            .get_selected_project_path_name()
-            .unwrap();
-        let mut project_path = self.active_folder_path.clone();
-        project_path = project_path.join(String::from(".").add(utils::PROJECT_FILE_EXTENSION));
-        project_path = project_path.with_file_name(project_name);
-        project_path.set_extension(utils::PROJECT_FILE_EXTENSION);
-        let text_active_path = Text::from(project_path.to_str().unwrap());
-        let task_window_ref = &mut self.task_window;
-        self.terminal
-            .draw(|f| {
-                let window_layout = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints(
-                        [
-                            Constraint::Percentage(5),
-                            Constraint::Percentage(90),
-                            Constraint::Percentage(5),
-                        ]
-                        .as_ref(),
-                    )
-                    .split(f.size());
-                let current_project_path = Paragraph::new(text_active_path);
-                f.render_widget(current_project_path, window_layout[0]);
-                let controls_string =
-                    String::from(task_window_ref.get_controls_description().as_str());
-                let controls_para = Paragraph::new(Text::from(controls_string));
-                f.render_widget(controls_para, window_layout[2]);
-                task_window_ref.display(f, window_layout[1]);
-            })
-            .unwrap();
+    /// Draws the selected window, with the help and the command palette over it when open.
+    fn display_window(&mut self) {
+        let location = match self.selected_window {
+            SelectedWindow::Task => {
+                let project_name = self
+                    .project_window
+                    .get_selected_project_path_name()
+                    .unwrap();
+                self.task_window.get_project_location(project_name)
+            }
+            _ => String::from(self.active_folder_path.to_str().unwrap()),
+        };
+        let controls_string = self.get_controls_description();
+        let window: &dyn Drawable = match self.selected_window {
+            SelectedWindow::Project => &self.project_window,
+            SelectedWindow::Task => &self.task_window,
+            SelectedWindow::Dashboard => &self.dashboard_window,
+        };
+        let mut popups: Vec<&dyn Drawable> = vec![];
+        if self.help_popup.is_active() {
+            popups.push(&self.help_popup);
+        }
+        if self.command_palette.is_active() {
+            popups.push(&self.command_palette);
+        }
+        draw_window(
+            &mut self.terminal,
+            window,
+            location,
+            controls_string,
+            &popups,
+            &self.theme,
+        );
     }
 
     fn switch_to_window(&mut self, new_window: SelectedWindow) {
@@ -115,57 +172,194 @@ impl<'a> Application<'a> {
         match self.selected_window {
             SelectedWindow::Project => {
                 self.project_window =
-                    ProjectManagementService::new(self.active_folder_path.clone());
+                    ProjectManagementService::new(open_storage(&self.active_folder_path));
             }
             SelectedWindow::Task => {
                 match self.project_window.get_selected_project_path_name() {
                     Some(project_name) => {
                         self.task_window =
-                            TaskService::new(self.active_folder_path.clone(), project_name)
+                            TaskService::new(open_storage(&self.active_folder_path), project_name)
                     }
                     None => self.selected_window = SelectedWindow::Project,
                 };
             }
+            SelectedWindow::Dashboard => {
+                self.dashboard_window =
+                    DashboardService::new(open_storage(&self.active_folder_path));
+            }
         }
     }
 
     pub fn update(&mut self) {
+        self.display_window();
+    }
+    fn get_window_bindings(&self) -> &'static [KeyBinding<WindowAction>] {
         match self.selected_window {
-            SelectedWindow::Project => {
-                self.display_main_window();
+            SelectedWindow::Project => PROJECT_WINDOW_BINDINGS,
+            SelectedWindow::Task => TASK_WINDOW_BINDINGS,
+            SelectedWindow::Dashboard => DASHBOARD_WINDOW_BINDINGS,
+        }
+    }
+
+    fn get_window_input_mode(&self) -> InputMode {
+        match self.selected_window {
+            SelectedWindow::Project => self.project_window.get_input_mode(),
+            SelectedWindow::Task => self.task_window.get_input_mode(),
+            SelectedWindow::Dashboard => self.dashboard_window.get_input_mode(),
+        }
+    }
+
+    fn get_controls_description(&self) -> String {
+        if self.command_palette.is_active() {
+            return self.command_palette.get_controls_description();
+        } else if self.help_popup.is_active() {
+            return self.help_popup.get_controls_description();
+        }
+        let window_controls = match self.selected_window {
+            SelectedWindow::Project => self.project_window.get_controls_description(),
+            SelectedWindow::Task => self.task_window.get_controls_description(),
+            SelectedWindow::Dashboard => self.dashboard_window.get_controls_description(),
+        };
+        match self.get_window_input_mode() {
+            InputMode::CommandMode => format!(
+                "{} | {}: {} | {}",
+                describe_controls(self.get_window_bindings()),
+                COMMAND_PALETTE_LABEL,
+                COMMAND_PALETTE_DESCRIPTION,
+                window_controls
+            ),
+            InputMode::WriteMode => window_controls,
+        }
+    }
+
+    /// The keys of the open popup, of the window and of the application.
+    fn get_help_sections(&self) -> Vec<HelpSection> {
+        let mut sections = match self.selected_window {
+            SelectedWindow::Project => self.project_window.get_help_sections(),
+            SelectedWindow::Task => self.task_window.get_help_sections(),
+            SelectedWindow::Dashboard => self.dashboard_window.get_help_sections(),
+        };
+        let mut general = HelpSection::from_bindings("General", self.get_window_bindings());
+        general.entries.push(HelpEntry::describe_only(
+            COMMAND_PALETTE_LABEL,
+            COMMAND_PALETTE_DESCRIPTION,
+        ));
+        sections.push(general);
+        sections
+    }
+
+    fn show_help(&mut self) {
+        self.help_popup = PopupHelpWindow::new(self.get_help_sections());
+    }
+
+    /// Opens the palette of every command that can be run from where the user is.
+    pub fn show_command_palette(&mut self) {
+        self.help_popup.set_active(false);
+        self.command_palette = PopupCommandPalette::new(self.get_help_sections());
+    }
+
+    pub fn handle_inputs(&mut self, key_code: KeyCode) {
+        if self.command_palette.is_active() {
+            self.command_palette.handle_input_key(key_code);
+            if self.command_palette.is_completed() {
+                self.command_palette.set_active(false);
+                // Runs the command as its key would, the palette being gone.
+                if let Some(chosen_key) = self.command_palette.get_chosen_key() {
+                    self.handle_inputs(chosen_key);
+                }
             }
-            SelectedWindow::Task => {
-                self.display_tasks_window();
+            return;
+        }
+        if self.help_popup.is_active() {
+            self.help_popup.handle_input_key(key_code);
+            if self.help_popup.is_completed() {
+                self.help_popup.set_active(false);
+            }
+            return;
+        }
+        let window_action = find_action(self.get_window_bindings(), key_code);
+        if let Some(WindowAction::Help) = window_action {
+            // '?' is typed like any other character when writing, F1 works everywhere.
+            if let (InputMode::CommandMode, _) | (_, KeyCode::F(1)) =
+                (self.get_window_input_mode(), key_code)
+            {
+                self.show_help();
+                return;
+            }
+        }
+        match self.selected_window {
+            SelectedWindow::Project => self.project_window.handle_input_key(key_code),
+            SelectedWindow::Task => self.task_window.handle_input_key(key_code),
+            SelectedWindow::Dashboard => self.dashboard_window.handle_input_key(key_code),
+        }
+        if let InputMode::CommandMode = self.get_window_input_mode() {
+            match window_action {
+                Some(WindowAction::Quit) => self.quit(),
+                Some(WindowAction::OpenTasks) => self.switch_to_window(SelectedWindow::Task),
+                Some(WindowAction::OpenStatistics) => {
+                    self.switch_to_window(SelectedWindow::Dashboard)
+                }
+                Some(WindowAction::BackToProjects) => {
+                    self.switch_to_window(SelectedWindow::Project)
+                }
+                Some(WindowAction::Help) | None => {}
             }
         }
     }
-    pub fn handle_inputs(&mut self, key_code: KeyCode) {
+    pub fn handle_mouse(&mut self, mouse_event: MouseEvent) {
+        if self.command_palette.is_active() {
+            match mouse_event.kind {
+                MouseEventKind::ScrollUp => self.command_palette.select_previous(),
+                MouseEventKind::ScrollDown => self.command_palette.select_next(),
+                MouseEventKind::Down(MouseButton::Left) => {
+                    self.command_palette
+                        .click(mouse_event.column, mouse_event.row);
+                    if self.command_palette.is_completed() {
+                        self.handle_inputs(KeyCode::Enter);
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+        if self.help_popup.is_active() {
+            match mouse_event.kind {
+                MouseEventKind::ScrollUp => self.help_popup.scroll_by(-1),
+                MouseEventKind::ScrollDown => self.help_popup.scroll_by(1),
+                _ => {}
+            }
+            return;
+        }
         match self.selected_window {
             SelectedWindow::Project => {
-                self.project_window.handle_input_key(key_code);
-                match self.project_window.get_input_mode() {
-                    InputMode::CommandMode => match key_code {
-                        KeyCode::Char('q') => self.quit(),
-                        KeyCode::Tab => self.switch_to_window(SelectedWindow::Task),
-                        KeyCode::Enter => self.switch_to_window(SelectedWindow::Task),
-                        _ => {}
-                    },
-                    _ => {}
+                self.project_window.handle_mouse_event(mouse_event);
+                if self.project_window.take_open_request() {
+                    self.switch_to_window(SelectedWindow::Task);
                 }
             }
-            SelectedWindow::Task => {
-                self.task_window.handle_input_key(key_code);
-                match self.task_window.get_input_mode() {
-                    InputMode::CommandMode => match key_code {
-                        KeyCode::Char('q') => self.quit(),
-                        KeyCode::Tab => self.switch_to_window(SelectedWindow::Project),
-                        _ => {}
-                    },
-                    _ => {}
-                }
+            SelectedWindow::Task => self.task_window.handle_mouse_event(mouse_event),
+            SelectedWindow::Dashboard => self.dashboard_window.handle_mouse_event(mouse_event),
+        }
+    }
+
+    /// Types the pasted text in the input being written, if any. Line breaks are left out so
+    /// a paste cannot validate the input.
+    pub fn handle_paste(&mut self, text: &str) {
+        if self.command_palette.is_active() {
+            let text: String = text.chars().filter(|c| !c.is_control()).collect();
+            self.command_palette.push_query(text.as_str());
+            return;
+        }
+        if self.help_popup.is_active() {
+            return;
+        }
+        if let InputMode::WriteMode = self.get_window_input_mode() {
+            for character in text.chars().filter(|character| !character.is_control()) {
+                self.handle_inputs(KeyCode::Char(character));
             }
         }
     }
+
     pub fn quit(&mut self) {
         self.is_running = false;
         match self.terminal.flush() {
@@ -175,6 +369,57 @@ impl<'a> Application<'a> {
     }
 }
 
+/// Draws `window` between the line showing its `location` and the controls, then the `popups`
+/// over it.
+fn draw_window(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    window: &dyn Drawable,
+    location: String,
+    controls_string: String,
+    popups: &[&dyn Drawable],
+    theme: &Theme,
+) {
+    terminal
+        .draw(|f| {
+            let window_layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Percentage(5),
+                        Constraint::Percentage(90),
+                        Constraint::Percentage(5),
+                    ]
+                    .as_ref(),
+                )
+                .split(f.size());
+            let current_project_path = Paragraph::new(Text::from(location));
+            f.render_widget(current_project_path, window_layout[0]);
+            let controls_para = Paragraph::new(Text::from(controls_string));
+            f.render_widget(controls_para, window_layout[2]);
+            window.display(f, window_layout[1], theme);
+            for popup in popups.iter() {
+                popup.display(f, window_layout[1], theme);
+            }
+        })
+        .unwrap();
+}
+
+impl<'a> Drop for Application<'a> {
+    /// Leaves the terminal as it was found, however the application stops.
+    fn drop(&mut self) {
+        if let Err(e) = terminal::restore_terminal() {
+            eprintln!("Error when restoring the terminal: {}", e);
+        }
+    }
+}
+
+fn open_storage(path: &Path) -> Box<dyn Storage> {
+    match storage::open_storage(path.to_path_buf()) {
+        Ok(opened_storage) => opened_storage,
+        Err(e) => panic!("Error occurred while opening the project storage: {}", e),
+    }
+}
+
 trait InformationDisplay {
     fn get_description(&self) -> String;
     fn get_name(&self) -> String;
@@ -194,18 +439,74 @@ pub struct Project {
     pub description: String,
     pub active_tasks: Vec<Task>,
     pub completed_tasks: Vec<Task>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Archived projects are hidden from the project list and the statistics unless asked for.
+    #[serde(default)]
+    pub archived: bool,
+    /// Name of the project or group this project is a sub-project of.
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Sub-folder of the working folder the file of the project is in, grouping the project when
+    /// it has no parent. It is known from where the file is and never saved.
+    #[serde(skip)]
+    pub folder_group: Option<String>,
 }
 
 impl Project {
     pub fn new(project_name: String) -> Project {
+        let now = Utc::now();
         Project {
             name: project_name,
             description: String::from("Sample description"),
             active_tasks: vec![],
             completed_tasks: vec![],
+            created_at: Some(now),
+            updated_at: Some(now),
+            completed_at: None,
+            archived: false,
+            parent: None,
+            folder_group: None,
         }
     }
 
+    /// Records a modification of the project. The project counts as completed from the moment
+    /// its last active task is completed until a task is added or reopened.
+    pub fn mark_updated(&mut self) {
+        let now = Utc::now();
+        self.updated_at = Some(now);
+        self.completed_at = match self.active_tasks.is_empty() && !self.completed_tasks.is_empty() {
+            true => self.completed_at.or(Some(now)),
+            false => None,
+        };
+    }
+
+    /// Gives the projects written before timestamps existed the time they were last saved.
+    /// The completion time of their tasks stays unknown so they do not all count as done that day.
+    pub fn fill_missing_timestamps(&mut self, saved_at: DateTime<Utc>) {
+        self.created_at.get_or_insert(saved_at);
+        self.updated_at.get_or_insert(saved_at);
+        if self.active_tasks.is_empty() && !self.completed_tasks.is_empty() {
+            self.completed_at.get_or_insert(saved_at);
+        }
+        for task in self
+            .active_tasks
+            .iter_mut()
+            .chain(self.completed_tasks.iter_mut())
+        {
+            task.created_at.get_or_insert(saved_at);
+            task.updated_at.get_or_insert(saved_at);
+        }
+    }
+
+    pub fn get_timestamps_description(&self) -> String {
+        format_timestamps(self.created_at, self.updated_at, self.completed_at)
+    }
+
     pub fn write_project_full_path(&self, path_for_project: PathBuf) -> Result<(), std::io::Error> {
         let project_string = match serde_json::to_string(self) {
             Ok(p_string) => p_string,
@@ -232,23 +533,222 @@ impl TaskContainer for Project {
 
 #[derive(Clone, Default, Serialize, Deserialize)]
 pub struct Task {
+    #[serde(default = "Uuid::new_v4")]
+    pub id: Uuid,
     pub name: String,
     pub description: String,
     pub time_spent: i32,
     pub estimate: i32,
     pub sub_tasks: Vec<Task>,
+    #[serde(default)]
+    pub priority: Option<char>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub due_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// When the previous instances of a recurring task were completed, oldest first.
+    #[serde(default)]
+    pub completion_history: Vec<DateTime<Utc>>,
+    /// Ids of the tasks, from any project, that have to be completed before this one.
+    #[serde(default)]
+    pub depends_on: Vec<Uuid>,
+    /// Comments added over time, oldest first, leaving the description as it is.
+    #[serde(default)]
+    pub notes: Vec<TaskNote>,
+    /// Steps lighter than sub tasks, such as acceptance criteria.
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub done: bool,
+}
+
+/// A comment on a task, such as a decision or a progress report.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TaskNote {
+    pub at: DateTime<Utc>,
+    pub author: String,
+    pub text: String,
+}
+
+impl TaskNote {
+    pub fn new(text: String) -> TaskNote {
+        TaskNote {
+            at: Utc::now(),
+            author: activity::get_user_name(),
+            text,
+        }
+    }
+
+    /// The note on a single line as exports write it: its RFC 3339 time, its author and its text.
+    pub fn to_line(&self) -> String {
+        format!("{} {}: {}", self.at.to_rfc3339(), self.author, self.text)
+    }
+
+    /// Reads a line written by `to_line`.
+    pub fn from_line(line: &str) -> Option<TaskNote> {
+        let (timestamp, rest) = line.trim().split_once(' ')?;
+        let (author, text) = rest.split_once(": ")?;
+        Some(TaskNote {
+            at: DateTime::parse_from_rfc3339(timestamp)
+                .ok()?
+                .with_timezone(&Utc),
+            author: String::from(author),
+            text: String::from(text),
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {}: {}",
+            format_timestamp(Some(self.at)),
+            self.author,
+            self.text
+        )
+    }
 }
 
 impl Task {
     pub fn new(task_name: String, task_description: String) -> Task {
+        let now = Utc::now();
         Task {
+            id: Uuid::new_v4(),
             name: task_name,
             description: task_description,
             time_spent: 0,
             estimate: 0,
             sub_tasks: vec![],
+            priority: None,
+            tags: vec![],
+            due_date: None,
+            created_at: Some(now),
+            updated_at: Some(now),
+            completed_at: None,
+            recurrence: None,
+            completion_history: vec![],
+            depends_on: vec![],
+            notes: vec![],
+            checklist: vec![],
+        }
+    }
+
+    /// The instance following this recurring task once it is completed, due on the next day
+    /// of its rule and carrying the completion history of the previous instances.
+    pub fn get_next_occurrence(&self) -> Option<Task> {
+        let recurrence = self.recurrence.as_ref()?;
+        let completed_at = self.completed_at?;
+        let mut next = Task::new(self.name.clone(), self.description.clone());
+        next.estimate = self.estimate;
+        next.priority = self.priority;
+        next.tags = self.tags.clone();
+        next.sub_tasks = self.sub_tasks.clone();
+        next.checklist = self
+            .checklist
+            .iter()
+            .map(|item| ChecklistItem {
+                text: item.text.clone(),
+                done: false,
+            })
+            .collect();
+        next.recurrence = Some(recurrence.clone());
+        next.due_date = Some(recurrence.get_next_due_date(
+            self.due_date,
+            completed_at.with_timezone(&Local).date_naive(),
+        ));
+        next.completion_history = self.completion_history.clone();
+        next.completion_history.push(completed_at);
+        Some(next)
+    }
+
+    /// Due date, repetition and past completions, `None` for tasks that have none of them.
+    pub fn get_schedule_description(&self) -> Option<String> {
+        let mut parts: Vec<String> = vec![];
+        if let Some(due_date) = self.due_date {
+            parts.push(format!("Due: {}", due_date.format("%Y-%m-%d")));
+        }
+        if let Some(recurrence) = &self.recurrence {
+            parts.push(recurrence.describe());
+        }
+        if let Some(last_completion) = self.completion_history.last() {
+            parts.push(format!(
+                "Done {} time(s), last on {}",
+                self.completion_history.len(),
+                format_timestamp(Some(*last_completion))
+            ));
+        }
+        match parts.is_empty() {
+            true => None,
+            false => Some(parts.join(" | ")),
+        }
+    }
+
+    /// Items done out of the items of the checklist, such as `3/5`, `None` without a checklist.
+    pub fn get_checklist_progress(&self) -> Option<String> {
+        match self.checklist.is_empty() {
+            true => None,
+            false => Some(format!(
+                "{}/{}",
+                self.checklist.iter().filter(|item| item.done).count(),
+                self.checklist.len()
+            )),
         }
     }
+
+    pub fn mark_updated(&mut self) {
+        self.updated_at = Some(Utc::now());
+    }
+
+    pub fn mark_completed(&mut self) {
+        let now = Utc::now();
+        self.completed_at = Some(now);
+        self.updated_at = Some(now);
+    }
+
+    pub fn mark_reopened(&mut self) {
+        self.completed_at = None;
+        self.mark_updated();
+    }
+
+    pub fn get_timestamps_description(&self) -> String {
+        format_timestamps(self.created_at, self.updated_at, self.completed_at)
+    }
+}
+
+fn format_timestamp(timestamp: Option<DateTime<Utc>>) -> String {
+    match timestamp {
+        Some(timestamp) => timestamp
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        None => String::from("unknown"),
+    }
+}
+
+fn format_timestamps(
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+    completed_at: Option<DateTime<Utc>>,
+) -> String {
+    let mut description = format!(
+        "Created: {} | Updated: {}",
+        format_timestamp(created_at),
+        format_timestamp(updated_at)
+    );
+    if completed_at.is_some() {
+        description.push_str(format!(" | Completed: {}", format_timestamp(completed_at)).as_str());
+    }
+    description
 }
 
 impl InformationDisplay for Task {
@@ -263,13 +763,34 @@ impl InformationDisplay for Task {
 
 impl TaskContainer for Task {
     fn add_task(&mut self, task_name: String, task_description: String) {
-        let task = Task {
-            name: task_name,
-            description: task_description,
-            time_spent: 0,
-            estimate: 0,
-            sub_tasks: vec![],
-        };
+        let task = Task::new(task_name, task_description);
         self.sub_tasks.push(task);
     }
 }
+
+#[test]
+fn project_timestamps() {
+    let saved_at = Utc::now() - chrono::Duration::days(3);
+    let mut project: Project = serde_json::from_str(
+        r#"{"name": "Old", "description": "", "active_tasks": [],
+            "completed_tasks": [{"name": "Done", "description": "", "time_spent": 0,
+            "estimate": 0, "sub_tasks": []}]}"#,
+    )
+    .unwrap();
+    project.fill_missing_timestamps(saved_at);
+    assert_eq!(project.created_at, Some(saved_at));
+    assert_eq!(project.updated_at, Some(saved_at));
+    assert_eq!(project.completed_at, Some(saved_at));
+    assert_eq!(project.completed_tasks[0].created_at, Some(saved_at));
+    assert_eq!(project.completed_tasks[0].completed_at, None);
+
+    project.mark_updated();
+    assert!(project.updated_at > Some(saved_at));
+    assert_eq!(project.completed_at, Some(saved_at));
+    project.add_task(String::from("More"), String::new());
+    project.mark_updated();
+    assert_eq!(project.completed_at, None);
+    project.active_tasks.clear();
+    project.mark_updated();
+    assert_eq!(project.completed_at, project.updated_at);
+}
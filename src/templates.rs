@@ -0,0 +1,193 @@
+use crate::structure::{Project, Task};
+use crate::utils;
+use std::collections::HashMap;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Folder of the working folder holding the templates, hidden so it is not read as a group.
+pub static TEMPLATES_FOLDER_NAME: &str = ".templates";
+
+/// The templates folder next to the projects, `location` being the working folder or the
+/// database inside it.
+pub fn get_templates_folder(location: &Path) -> PathBuf {
+    match location.is_file() {
+        true => location
+            .parent()
+            .unwrap_or(location)
+            .join(TEMPLATES_FOLDER_NAME),
+        false => location.join(TEMPLATES_FOLDER_NAME),
+    }
+}
+
+/// Names of the `.pman` templates of the folder, sorted.
+pub fn list_templates(templates_folder: &Path) -> Vec<String> {
+    let mut names: Vec<String> = match std::fs::read_dir(templates_folder) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .map(|ext| ext == utils::PROJECT_FILE_EXTENSION)
+                    == Some(true)
+            })
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+            .collect(),
+        Err(_) => vec![],
+    };
+    names.sort();
+    names
+}
+
+pub fn load_template(templates_folder: &Path, template_name: &str) -> Result<Project, Error> {
+    let template_path = templates_folder.join(format!(
+        "{}.{}",
+        template_name,
+        utils::PROJECT_FILE_EXTENSION
+    ));
+    utils::load_project_from_path(template_path)
+}
+
+/// Calls `on_placeholder` with the name of every `{{name}}` of the text and returns the text
+/// with each placeholder replaced by what it returned.
+fn replace_placeholders(text: &str, mut on_placeholder: impl FnMut(&str) -> String) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after_start = &rest[start + 2..];
+        let end = match after_start.find("}}") {
+            Some(end) => end,
+            None => break,
+        };
+        result.push_str(&rest[..start]);
+        result.push_str(on_placeholder(after_start[..end].trim()).as_str());
+        rest = &after_start[end + 2..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn get_task_texts(task: &Task) -> Vec<&String> {
+    let mut texts = vec![&task.name, &task.description];
+    texts.extend(task.tags.iter());
+    for sub_task in task.sub_tasks.iter() {
+        texts.extend(get_task_texts(sub_task));
+    }
+    texts
+}
+
+/// The placeholder variables of the template, in the order they first appear.
+pub fn get_variables(template: &Project) -> Vec<String> {
+    let mut texts = vec![&template.name, &template.description];
+    texts.extend(template.parent.iter());
+    for task in template.active_tasks.iter() {
+        texts.extend(get_task_texts(task));
+    }
+    let mut variables: Vec<String> = vec![];
+    for text in texts {
+        replace_placeholders(text, |variable| {
+            if !variables.iter().any(|v| v == variable) {
+                variables.push(variable.to_string());
+            }
+            String::new()
+        });
+    }
+    variables
+}
+
+/// The text with the placeholders of known variables filled in, others being left as written.
+pub fn fill_placeholders(text: &str, values: &HashMap<String, String>) -> String {
+    replace_placeholders(text, |variable| match values.get(variable) {
+        Some(value) => value.clone(),
+        None => format!("{{{{{}}}}}", variable),
+    })
+}
+
+fn create_task(
+    template_task: &Task,
+    values: &HashMap<String, String>,
+    task_ids: &mut HashMap<Uuid, Uuid>,
+) -> Task {
+    let mut task = Task::new(
+        fill_placeholders(&template_task.name, values),
+        fill_placeholders(&template_task.description, values),
+    );
+    task_ids.insert(template_task.id, task.id);
+    task.estimate = template_task.estimate;
+    task.priority = template_task.priority;
+    task.tags = template_task
+        .tags
+        .iter()
+        .map(|tag| fill_placeholders(tag, values))
+        .collect();
+    task.recurrence = template_task.recurrence.clone();
+    task.depends_on = template_task.depends_on.clone();
+    task.sub_tasks = template_task
+        .sub_tasks
+        .iter()
+        .map(|sub_task| create_task(sub_task, values, task_ids))
+        .collect();
+    task
+}
+
+/// A new project with the description and active tasks of the template, placeholders filled
+/// in. Tasks get new ids, dependencies between them following, and due dates are left out.
+pub fn create_project(
+    template: &Project,
+    values: &HashMap<String, String>,
+    project_name: String,
+) -> Project {
+    let mut project = Project::new(project_name);
+    project.description = fill_placeholders(&template.description, values);
+    project.parent = template
+        .parent
+        .as_ref()
+        .map(|parent| fill_placeholders(parent, values));
+    let mut task_ids: HashMap<Uuid, Uuid> = HashMap::new();
+    project.active_tasks = template
+        .active_tasks
+        .iter()
+        .map(|task| create_task(task, values, &mut task_ids))
+        .collect();
+    for task in project.active_tasks.iter_mut() {
+        task.depends_on = task
+            .depends_on
+            .iter()
+            .map(|id| *task_ids.get(id).unwrap_or(id))
+            .collect();
+    }
+    project
+}
+
+#[test]
+fn project_from_template() {
+    let mut template = Project::new(String::from("Release {{version}}"));
+    template.description = String::from("Shipping {{ version }} of {{product}}");
+    let build = Task::new(String::from("Build {{version}}"), String::new());
+    let mut announce = Task::new(String::from("Announce"), String::from("{{unknown"));
+    announce.depends_on = vec![build.id];
+    template.active_tasks = vec![build, announce];
+    assert_eq!(get_variables(&template), vec!["version", "product"]);
+
+    let values: HashMap<String, String> = vec![
+        (String::from("version"), String::from("1.2")),
+        (String::from("product"), String::from("pman")),
+    ]
+    .into_iter()
+    .collect();
+    let name = fill_placeholders(&template.name, &values);
+    let project = create_project(&template, &values, name);
+    assert_eq!(project.name, "Release 1.2");
+    assert_eq!(project.description, "Shipping 1.2 of pman");
+    assert_eq!(project.active_tasks[0].name, "Build 1.2");
+    assert_eq!(project.active_tasks[1].description, "{{unknown");
+    assert_eq!(
+        project.active_tasks[1].depends_on,
+        vec![project.active_tasks[0].id]
+    );
+    assert_ne!(project.active_tasks[0].id, template.active_tasks[0].id);
+    assert_eq!(
+        fill_placeholders("{{version}} {{other}}", &values),
+        "1.2 {{other}}"
+    );
+}
@@ -0,0 +1,69 @@
+use crossterm::cursor::{Hide, Show};
+use crossterm::event::{
+    DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+    EnableFocusChange, EnableMouseCapture,
+};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use std::io::{self, Error};
+
+/// Puts the terminal in the state the interface is drawn in: raw mode so keys are read as they
+/// are pressed without being echoed, the alternate screen so the shell is left as it was, mouse,
+/// focus and paste events reported and the cursor hidden.
+pub fn setup_terminal() -> Result<(), Error> {
+    enable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableFocusChange,
+        EnableBracketedPaste,
+        Hide
+    )
+}
+
+/// Gives the terminal back to the shell, every step being attempted even when one fails.
+pub fn restore_terminal() -> Result<(), Error> {
+    let screen_result = execute!(
+        io::stdout(),
+        DisableBracketedPaste,
+        DisableFocusChange,
+        DisableMouseCapture,
+        LeaveAlternateScreen,
+        Show
+    );
+    let raw_mode_result = disable_raw_mode();
+    screen_result.and(raw_mode_result)
+}
+
+/// Restores the terminal before a panic is printed, so the message can be read and the shell
+/// is usable afterwards.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        let _ = restore_terminal();
+        default_hook(panic_info);
+    }));
+}
+
+/// Calls `on_signal` from its own thread whenever the process is asked to stop by SIGTERM,
+/// SIGINT or SIGHUP.
+#[cfg(unix)]
+pub fn handle_termination_signals(on_signal: impl Fn() + Send + 'static) -> Result<(), Error> {
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+    let mut signals = signal_hook::iterator::Signals::new([SIGTERM, SIGINT, SIGHUP])?;
+    std::thread::spawn(move || {
+        for _ in signals.forever() {
+            on_signal();
+        }
+    });
+    Ok(())
+}
+
+/// Ctrl-C is read as a key in raw mode, there are no other signals to handle.
+#[cfg(not(unix))]
+pub fn handle_termination_signals(_on_signal: impl Fn() + Send + 'static) -> Result<(), Error> {
+    Ok(())
+}
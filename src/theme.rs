@@ -0,0 +1,276 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tui::style::{Color, Modifier, Style};
+
+pub static DEFAULT_THEME_NAME: &str = "dark";
+pub static BUILTIN_THEME_NAMES: [&str; 4] = ["dark", "light", "high-contrast", "no-color"];
+
+/// Colors the interface is drawn with. `Color::Reset` leaves the terminal color, the styles then
+/// falling back to modifiers so that selections and emphasis stay visible.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    /// Background of the selected item of a list.
+    pub selection: Color,
+    pub selection_text: Color,
+    /// Items that cannot be worked on, such as blocked tasks and archived projects.
+    pub muted: Color,
+    /// Gauges, bars and charts, and the frame and buttons of popups.
+    pub accent: Color,
+    /// Text written over the accent color, and the empty part of gauges.
+    pub accent_text: Color,
+    /// Choices asking for a confirmation.
+    pub warning: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            selection: Color::Green,
+            selection_text: Color::Reset,
+            muted: Color::DarkGray,
+            accent: Color::Green,
+            accent_text: Color::Black,
+            warning: Color::Red,
+        }
+    }
+}
+
+impl Theme {
+    pub fn get_builtin(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::default()),
+            "light" => Some(Theme {
+                selection: Color::Blue,
+                selection_text: Color::White,
+                muted: Color::Gray,
+                accent: Color::Blue,
+                accent_text: Color::White,
+                warning: Color::Red,
+            }),
+            "high-contrast" => Some(Theme {
+                selection: Color::Yellow,
+                selection_text: Color::Black,
+                muted: Color::Gray,
+                accent: Color::White,
+                accent_text: Color::Black,
+                warning: Color::LightRed,
+            }),
+            "no-color" => Some(Theme {
+                selection: Color::Reset,
+                selection_text: Color::Reset,
+                muted: Color::Reset,
+                accent: Color::Reset,
+                accent_text: Color::Reset,
+                warning: Color::Reset,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn selection_style(&self) -> Style {
+        let style = Style::default()
+            .fg(self.selection_text)
+            .bg(self.selection)
+            .add_modifier(Modifier::BOLD);
+        match self.selection {
+            Color::Reset => style.add_modifier(Modifier::REVERSED),
+            _ => style,
+        }
+    }
+
+    pub fn muted_style(&self) -> Style {
+        match self.muted {
+            Color::Reset => Style::default().add_modifier(Modifier::DIM),
+            _ => Style::default().fg(self.muted),
+        }
+    }
+
+    pub fn accent_style(&self) -> Style {
+        Style::default().fg(self.accent)
+    }
+
+    /// Values written over bars of the accent color.
+    pub fn accent_value_style(&self) -> Style {
+        let style = Style::default().fg(self.accent_text).bg(self.accent);
+        match self.accent {
+            Color::Reset => style.add_modifier(Modifier::REVERSED),
+            _ => style,
+        }
+    }
+
+    pub fn gauge_style(&self) -> Style {
+        Style::default().fg(self.accent).bg(self.accent_text)
+    }
+
+    pub fn warning_style(&self) -> Style {
+        match self.warning {
+            Color::Reset => Style::default().add_modifier(Modifier::BOLD),
+            _ => Style::default().fg(self.warning),
+        }
+    }
+
+    /// Sets the color of a role as written in the configuration file.
+    fn set_color(&mut self, role: &str, color: Color) -> Result<(), String> {
+        match role {
+            "selection" => self.selection = color,
+            "selection_text" => self.selection_text = color,
+            "muted" => self.muted = color,
+            "accent" => self.accent = color,
+            "accent_text" => self.accent_text = color,
+            "warning" => self.warning = color,
+            _ => return Err(format!("Unknown theme color {}", role)),
+        }
+        Ok(())
+    }
+}
+
+/// Content of the configuration file, such as
+/// `{"theme": "mine", "themes": {"mine": {"base": "light", "selection": "#268bd2"}}}`.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub theme: Option<String>,
+    /// User themes, each giving colors over those of its `base` theme, dark if not given.
+    pub themes: HashMap<String, HashMap<String, String>>,
+}
+
+/// Reads a color from its name (`dark_gray`, `light-blue`, `reset`...), its 256 colors index
+/// or its `#rrggbb` value.
+pub fn parse_color(text: &str) -> Result<Color, String> {
+    let name = text.trim().to_lowercase().replace('-', "_");
+    let color = match name.as_str() {
+        "reset" | "default" | "none" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark_gray" | "dark_grey" => Color::DarkGray,
+        "light_red" => Color::LightRed,
+        "light_green" => Color::LightGreen,
+        "light_yellow" => Color::LightYellow,
+        "light_blue" => Color::LightBlue,
+        "light_magenta" => Color::LightMagenta,
+        "light_cyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => {
+            if let Some(hex) = name.strip_prefix('#') {
+                match (hex.len(), u32::from_str_radix(hex, 16)) {
+                    (6, Ok(value)) => {
+                        Color::Rgb((value >> 16) as u8, (value >> 8) as u8, value as u8)
+                    }
+                    _ => return Err(format!("Invalid color {}", text)),
+                }
+            } else {
+                match name.parse::<u8>() {
+                    Ok(index) => Color::Indexed(index),
+                    Err(_) => return Err(format!("Invalid color {}", text)),
+                }
+            }
+        }
+    };
+    Ok(color)
+}
+
+fn get_theme_of_name(config: &ThemeConfig, name: &str, depth: usize) -> Result<Theme, String> {
+    let colors = match config.themes.get(name) {
+        Some(colors) => colors,
+        None => {
+            return Theme::get_builtin(name).ok_or(format!(
+                "Unknown theme {}, the built-in themes are {}",
+                name,
+                BUILTIN_THEME_NAMES.join(", ")
+            ))
+        }
+    };
+    // A user theme may be based on another one, as long as they do not go around in circles.
+    if depth > config.themes.len() {
+        return Err(format!("The theme {} is based on itself", name));
+    }
+    let mut theme = match colors.get("base") {
+        Some(base) => get_theme_of_name(config, base.trim(), depth + 1)?,
+        None => Theme::default(),
+    };
+    for (role, color) in colors.iter().filter(|(role, _)| role.as_str() != "base") {
+        theme.set_color(role, parse_color(color)?)?;
+    }
+    Ok(theme)
+}
+
+/// The theme asked for in the configuration, colors being left out when `no_color` is set.
+pub fn select_theme(config: &ThemeConfig, no_color: bool) -> Result<Theme, String> {
+    if no_color {
+        return Ok(Theme::get_builtin("no-color").unwrap());
+    }
+    let name = config.theme.as_deref().unwrap_or(DEFAULT_THEME_NAME);
+    get_theme_of_name(config, name.trim(), 0)
+}
+
+pub fn get_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|folder| folder.join("pman").join("config.json"))
+}
+
+/// The theme of the configuration file, if there is one. Following https://no-color.org, any
+/// non-empty `NO_COLOR` environment variable turns colors off.
+pub fn load_theme() -> Result<Theme, String> {
+    let config = match get_config_path() {
+        Some(path) if path.exists() => {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+            serde_json::from_str(&content)
+                .map_err(|e| format!("Invalid configuration {}: {}", path.display(), e))?
+        }
+        _ => ThemeConfig::default(),
+    };
+    let no_color = std::env::var_os("NO_COLOR").map(|value| !value.is_empty()) == Some(true);
+    select_theme(&config, no_color).map_err(|e| match get_config_path() {
+        Some(path) => format!("{} in {}", e, path.display()),
+        None => e,
+    })
+}
+
+#[test]
+fn user_themes_and_no_color() {
+    let config: ThemeConfig = serde_json::from_str(
+        r##"{"theme": "mine", "themes": {
+            "mine": {"base": "light", "selection": "#268bd2", "muted": "dark-gray"},
+            "loop": {"base": "loop"}
+        }}"##,
+    )
+    .unwrap();
+    let theme = select_theme(&config, false).unwrap();
+    assert_eq!(theme.selection, Color::Rgb(0x26, 0x8b, 0xd2));
+    assert_eq!(theme.muted, Color::DarkGray);
+    assert_eq!(theme.accent, Theme::get_builtin("light").unwrap().accent);
+    assert_eq!(
+        select_theme(&config, true),
+        Ok(Theme::get_builtin("no-color").unwrap())
+    );
+    assert_eq!(
+        select_theme(&ThemeConfig::default(), false),
+        Ok(Theme::default())
+    );
+    let mut config = config;
+    config.theme = Some(String::from("loop"));
+    assert!(select_theme(&config, false).is_err());
+    assert_eq!(parse_color("42"), Ok(Color::Indexed(42)));
+    assert!(parse_color("#12345").is_err());
+
+    let no_color = Theme::get_builtin("no-color").unwrap();
+    let styles = [
+        no_color.selection_style(),
+        no_color.muted_style(),
+        no_color.accent_style(),
+        no_color.accent_value_style(),
+        no_color.gauge_style(),
+        no_color.warning_style(),
+    ];
+    for style in styles.iter() {
+        assert!(style.fg.is_none() || style.fg == Some(Color::Reset));
+        assert!(style.bg.is_none() || style.bg == Some(Color::Reset));
+    }
+}
@@ -2,17 +2,23 @@ use tui::backend::CrosstermBackend;
 use tui::Frame;
 
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
-use tui::text::Text;
-use tui::widgets::{Block, BorderType, Borders, Clear, ListState, Paragraph, Wrap};
+use tui::style::{Modifier, Style};
+use tui::text::{Span, Spans, Text};
+use tui::widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
 
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, MouseEvent};
+use std::cell::{Cell, RefCell};
 use std::io::Stdout;
-use tui::style::{Color, Style};
+
+use crate::theme::Theme;
 
 #[derive(Default)]
 pub struct DisplayList<T> {
     pub(crate) state: ListState,
     pub(crate) array: Vec<T>,
+    /// Where the list was last drawn and the rows each visible item took, for mouse clicks.
+    drawn_area: Cell<Rect>,
+    drawn_rows: RefCell<Vec<(u16, u16, usize)>>,
 }
 
 #[derive(SmartDefault)]
@@ -23,7 +29,7 @@ pub enum InputMode {
 }
 
 pub trait Drawable {
-    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect);
+    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect, theme: &Theme);
     fn centered_rect(&self, percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         let popup_layout = Layout::default()
             .direction(Direction::Vertical)
@@ -55,6 +61,106 @@ pub trait InputReceptor {
     fn handle_input_key(&mut self, key_code: KeyCode);
     fn get_controls_description(&self) -> String;
     fn get_input_mode(&self) -> InputMode;
+    /// The keys that currently apply, those of an open popup first.
+    fn get_help_sections(&self) -> Vec<HelpSection>;
+}
+
+/// Keys doing an action of a window. Windows look the pressed key up in their bindings, which
+/// also give the controls bar and the help, so what is shown is always what is handled.
+pub struct KeyBinding<A> {
+    pub keys: &'static [KeyCode],
+    /// How the keys are written in the controls bar and the help.
+    pub label: &'static str,
+    pub description: &'static str,
+    pub action: A,
+}
+
+impl<A> KeyBinding<A> {
+    pub const fn new(
+        keys: &'static [KeyCode],
+        label: &'static str,
+        description: &'static str,
+        action: A,
+    ) -> KeyBinding<A> {
+        KeyBinding {
+            keys,
+            label,
+            description,
+            action,
+        }
+    }
+}
+
+pub fn find_action<A: Copy>(bindings: &[KeyBinding<A>], key_code: KeyCode) -> Option<A> {
+    bindings
+        .iter()
+        .find(|binding| binding.keys.contains(&key_code))
+        .map(|binding| binding.action)
+}
+
+pub fn describe_controls<A>(bindings: &[KeyBinding<A>]) -> String {
+    bindings
+        .iter()
+        .map(|binding| format!("{}: {}", binding.label, binding.description))
+        .collect::<Vec<String>>()
+        .join(" | ")
+}
+
+#[derive(Clone, Default)]
+pub struct HelpEntry {
+    pub label: &'static str,
+    pub description: &'static str,
+    /// A key doing the action, for the command palette to run it. Entries describing keys
+    /// rather than an action have none.
+    pub key: Option<KeyCode>,
+}
+
+impl HelpEntry {
+    pub fn describe_only(label: &'static str, description: &'static str) -> HelpEntry {
+        HelpEntry {
+            label,
+            description,
+            key: None,
+        }
+    }
+}
+
+/// Bindings listed together in the help, under the name of the window or popup they belong to.
+pub struct HelpSection {
+    pub title: &'static str,
+    pub entries: Vec<HelpEntry>,
+}
+
+impl HelpSection {
+    pub fn from_bindings<A>(title: &'static str, bindings: &[KeyBinding<A>]) -> HelpSection {
+        HelpSection {
+            title,
+            entries: bindings
+                .iter()
+                .map(|binding| HelpEntry {
+                    label: binding.label,
+                    description: binding.description,
+                    key: binding.keys.first().copied(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Windows that react to mouse clicks and scrolling, hit-testing against the areas they were
+/// last drawn in.
+pub trait MouseReceptor {
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent);
+}
+
+/// Popups translate a click to the keys that have the same effect, so the window showing them
+/// acts on it the same way as on the keyboard.
+pub trait Clickable {
+    fn get_keys_for_click(&self, column: u16, row: u16) -> Vec<KeyCode>;
+}
+
+pub fn is_inside(area: Rect, column: u16, row: u16) -> bool {
+    column >= area.x && column < area.x + area.width && row >= area.y && row < area.y + area.height
 }
 
 pub trait InputReturn {
@@ -109,12 +215,67 @@ impl<T> DisplayList<T> {
         let mut dl = DisplayList {
             state: Default::default(),
             array: content,
+            drawn_area: Cell::default(),
+            drawn_rows: RefCell::default(),
         };
         if content_len > 0 {
             dl.state.select(Some(0));
         }
         dl
     }
+
+    /// Records where the list was drawn, inside a block with borders, given the height of each
+    /// item and the selection it was drawn with. The items shown are worked out the same way
+    /// as `List` does: from the first one, scrolled just enough for the selection to be shown.
+    pub(crate) fn set_drawn(&self, area: Rect, item_heights: &[usize], selected: Option<usize>) {
+        let inner_area = Block::default().borders(Borders::ALL).inner(area);
+        let max_height = inner_area.height as usize;
+        let mut start = 0;
+        let mut end = 0;
+        let mut height = 0;
+        for item_height in item_heights.iter() {
+            if height + item_height > max_height {
+                break;
+            }
+            height += item_height;
+            end += 1;
+        }
+        let selected = selected
+            .unwrap_or(0)
+            .min(item_heights.len().saturating_sub(1));
+        while selected >= end && end < item_heights.len() {
+            height += item_heights[end];
+            end += 1;
+            while height > max_height && start < end {
+                height -= item_heights[start];
+                start += 1;
+            }
+        }
+        let mut rows = vec![];
+        let mut row = inner_area.y;
+        for (index, item_height) in item_heights.iter().enumerate().take(end).skip(start) {
+            rows.push((row, row + *item_height as u16, index));
+            row += *item_height as u16;
+        }
+        self.drawn_area.set(inner_area);
+        self.drawn_rows.replace(rows);
+    }
+
+    pub(crate) fn is_drawn_at(&self, column: u16, row: u16) -> bool {
+        is_inside(self.drawn_area.get(), column, row)
+    }
+
+    /// The item drawn at the position of the terminal, if any.
+    pub(crate) fn get_index_at(&self, column: u16, row: u16) -> Option<usize> {
+        if !self.is_drawn_at(column, row) {
+            return None;
+        }
+        self.drawn_rows
+            .borrow()
+            .iter()
+            .find(|(first_row, end_row, _)| row >= *first_row && row < *end_row)
+            .map(|(_, _, index)| *index)
+    }
 }
 
 #[derive(Default)]
@@ -122,6 +283,7 @@ pub struct PopupMessageWindow {
     description: String,
     is_active: bool,
     is_done: bool,
+    ok_area: Cell<Rect>,
 }
 
 // PopupMessageWindow
@@ -132,6 +294,7 @@ impl PopupMessageWindow {
             description: popup_message,
             is_active: true,
             is_done: false,
+            ok_area: Cell::default(),
         }
     }
 }
@@ -155,10 +318,12 @@ impl Completable for PopupMessageWindow {
 }
 
 impl Drawable for PopupMessageWindow {
-    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect) {
+    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect, theme: &Theme) {
         let popup_layout = self.centered_rect(50, 25, layout);
         frame.render_widget(Clear, popup_layout);
-        let popup_block = Block::default().borders(Borders::ALL);
+        let popup_block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.accent_style());
         frame.render_widget(popup_block, popup_layout);
         let main_popup_layout = Layout::default()
             .direction(Direction::Vertical)
@@ -170,32 +335,61 @@ impl Drawable for PopupMessageWindow {
             .block(block)
             .wrap(Wrap { trim: false });
         frame.render_widget(description_paragraph, main_popup_layout[0]);
-        let block = Block::default().borders(Borders::ALL);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Thick)
+            .style(theme.accent_style());
         let ok_message = Paragraph::new(Text::from("Ok"))
             .wrap(Wrap { trim: false })
             .alignment(Alignment::Center)
             .block(block);
         frame.render_widget(ok_message, main_popup_layout[1]);
+        self.ok_area.set(main_popup_layout[1]);
+    }
+}
+
+impl Clickable for PopupMessageWindow {
+    fn get_keys_for_click(&self, column: u16, row: u16) -> Vec<KeyCode> {
+        match is_inside(self.ok_area.get(), column, row) {
+            true => vec![KeyCode::Enter],
+            false => vec![],
+        }
     }
 }
 
+#[derive(Clone, Copy)]
+enum MessageAction {
+    Continue,
+}
+
+static MESSAGE_BINDINGS: &[KeyBinding<MessageAction>] = &[KeyBinding::new(
+    &[KeyCode::Enter],
+    "Enter",
+    "Continue",
+    MessageAction::Continue,
+)];
+
 impl InputReceptor for PopupMessageWindow {
     fn handle_input_key(&mut self, key_code: KeyCode) {
-        match key_code {
-            KeyCode::Enter => {
+        match find_action(MESSAGE_BINDINGS, key_code) {
+            Some(MessageAction::Continue) => {
                 self.is_done = true;
             }
-            _ => {}
+            None => {}
         }
     }
 
     fn get_controls_description(&self) -> String {
-        String::from("Press enter to continue")
+        describe_controls(MESSAGE_BINDINGS)
     }
 
     fn get_input_mode(&self) -> InputMode {
         InputMode::CommandMode
     }
+
+    fn get_help_sections(&self) -> Vec<HelpSection> {
+        vec![HelpSection::from_bindings("Message", MESSAGE_BINDINGS)]
+    }
 }
 
 // PopupBinaryChoice
@@ -206,6 +400,8 @@ pub struct PopupBinaryChoice {
     current_choice: bool,
     is_completed: bool,
     is_active: bool,
+    yes_area: Cell<Rect>,
+    no_area: Cell<Rect>,
 }
 
 impl PopupBinaryChoice {
@@ -215,6 +411,8 @@ impl PopupBinaryChoice {
             current_choice: false,
             is_completed: false,
             is_active: true,
+            yes_area: Cell::default(),
+            no_area: Cell::default(),
         }
     }
 
@@ -223,23 +421,45 @@ impl PopupBinaryChoice {
     }
 }
 
+#[derive(Clone, Copy)]
+enum ChoiceAction {
+    Yes,
+    No,
+    Confirm,
+}
+
+static CHOICE_BINDINGS: &[KeyBinding<ChoiceAction>] = &[
+    KeyBinding::new(&[KeyCode::Left], "<-", "Go Left (Yes)", ChoiceAction::Yes),
+    KeyBinding::new(&[KeyCode::Right], "->", "Go Right (No)", ChoiceAction::No),
+    KeyBinding::new(
+        &[KeyCode::Enter],
+        "Enter",
+        "Confirm Selection",
+        ChoiceAction::Confirm,
+    ),
+];
+
 impl InputReceptor for PopupBinaryChoice {
     fn handle_input_key(&mut self, key_code: KeyCode) {
-        match key_code {
-            KeyCode::Left => self.current_choice = true,
-            KeyCode::Right => self.current_choice = false,
-            KeyCode::Enter => self.is_completed = true,
-            _ => {}
+        match find_action(CHOICE_BINDINGS, key_code) {
+            Some(ChoiceAction::Yes) => self.current_choice = true,
+            Some(ChoiceAction::No) => self.current_choice = false,
+            Some(ChoiceAction::Confirm) => self.is_completed = true,
+            None => {}
         }
     }
 
     fn get_controls_description(&self) -> String {
-        String::from("<-: Go Left  |  ->: Go Right  |  Enter: Confirm Selection ")
+        describe_controls(CHOICE_BINDINGS)
     }
 
     fn get_input_mode(&self) -> InputMode {
         InputMode::CommandMode
     }
+
+    fn get_help_sections(&self) -> Vec<HelpSection> {
+        vec![HelpSection::from_bindings("Confirmation", CHOICE_BINDINGS)]
+    }
 }
 
 impl Completable for PopupBinaryChoice {
@@ -261,7 +481,7 @@ impl Completable for PopupBinaryChoice {
 }
 
 impl Drawable for PopupBinaryChoice {
-    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect) {
+    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect, theme: &Theme) {
         let popup_layout = self.centered_rect(50, 20, layout);
         frame.render_widget(Clear, popup_layout);
         let main_split = Layout::default()
@@ -282,7 +502,7 @@ impl Drawable for PopupBinaryChoice {
         let choice_block = Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Thick)
-            .style(Style::default().fg(Color::Red));
+            .style(theme.warning_style());
         let mut yes_paragraph = Paragraph::new(Text::from("Yes")).alignment(Alignment::Center);
         let mut no_paragraph = Paragraph::new(Text::from("No")).alignment(Alignment::Center);
         if self.current_choice {
@@ -294,6 +514,20 @@ impl Drawable for PopupBinaryChoice {
         }
         frame.render_widget(yes_paragraph, choice_layout[0]);
         frame.render_widget(no_paragraph, choice_layout[1]);
+        self.yes_area.set(choice_layout[0]);
+        self.no_area.set(choice_layout[1]);
+    }
+}
+
+impl Clickable for PopupBinaryChoice {
+    fn get_keys_for_click(&self, column: u16, row: u16) -> Vec<KeyCode> {
+        if is_inside(self.yes_area.get(), column, row) {
+            vec![KeyCode::Left, KeyCode::Enter]
+        } else if is_inside(self.no_area.get(), column, row) {
+            vec![KeyCode::Right, KeyCode::Enter]
+        } else {
+            vec![]
+        }
     }
 }
 
@@ -321,10 +555,12 @@ impl PopupInputWindow {
 }
 
 impl Drawable for PopupInputWindow {
-    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect) {
+    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect, theme: &Theme) {
         let popup_layout = self.centered_rect(50, 25, layout);
         frame.render_widget(Clear, popup_layout);
-        let popup_block = Block::default().borders(Borders::ALL);
+        let popup_block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.accent_style());
         frame.render_widget(popup_block, popup_layout);
         let main_popup_layout = Layout::default()
             .direction(Direction::Vertical)
@@ -364,32 +600,511 @@ impl InputReturn for PopupInputWindow {
     }
 }
 
+#[derive(Clone, Copy)]
+enum TextInputAction {
+    Cancel,
+    Confirm,
+    Erase,
+}
+
+static TEXT_INPUT_BINDINGS: &[KeyBinding<TextInputAction>] = &[
+    KeyBinding::new(&[KeyCode::Esc], "Esc", "Cancel", TextInputAction::Cancel),
+    KeyBinding::new(
+        &[KeyCode::Enter],
+        "Enter",
+        "Confirm entry",
+        TextInputAction::Confirm,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Backspace],
+        "Backspace",
+        "Erase the last character",
+        TextInputAction::Erase,
+    ),
+];
+
 impl InputReceptor for PopupInputWindow {
     fn handle_input_key(&mut self, key_code: KeyCode) {
-        match key_code {
-            KeyCode::Char(char) => {
-                self.input_string.push(char);
+        match (find_action(TEXT_INPUT_BINDINGS, key_code), key_code) {
+            (Some(TextInputAction::Cancel), _) => {
+                self.set_active(false);
             }
-            KeyCode::Backspace => {
+            (Some(TextInputAction::Confirm), _) => {
+                self.message_input_finished = true;
+            }
+            (Some(TextInputAction::Erase), _) => {
                 if self.input_string.len() > 0 {
                     self.input_string.pop();
                 }
             }
-            KeyCode::Enter => {
-                self.message_input_finished = true;
-            }
-            KeyCode::Esc => {
-                self.set_active(false);
+            (None, KeyCode::Char(char)) => {
+                self.input_string.push(char);
             }
             _ => {}
         };
     }
 
     fn get_controls_description(&self) -> String {
-        String::from("esc - Cancel | Enter - Confirm entry")
+        describe_controls(TEXT_INPUT_BINDINGS)
+    }
+
+    fn get_input_mode(&self) -> InputMode {
+        InputMode::CommandMode
+    }
+
+    fn get_help_sections(&self) -> Vec<HelpSection> {
+        let mut section = HelpSection::from_bindings("Text input", TEXT_INPUT_BINDINGS);
+        section.entries.push(HelpEntry::describe_only(
+            "Other keys",
+            "Typed in the entry, as is pasted text",
+        ));
+        vec![section]
+    }
+}
+
+// PopupHelpWindow
+
+#[derive(Default)]
+pub struct PopupHelpWindow {
+    sections: Vec<HelpSection>,
+    scroll: u16,
+    is_active: bool,
+    is_done: bool,
+    /// Lines the popup showed when last drawn, so scrolling stops at the last line.
+    visible_lines: Cell<u16>,
+}
+
+impl PopupHelpWindow {
+    pub fn new(sections: Vec<HelpSection>) -> PopupHelpWindow {
+        PopupHelpWindow {
+            sections,
+            scroll: 0,
+            is_active: true,
+            is_done: false,
+            visible_lines: Cell::default(),
+        }
+    }
+
+    fn get_lines(&self, title_style: Style) -> Vec<Spans<'static>> {
+        let label_width = self
+            .sections
+            .iter()
+            .flat_map(|section| section.entries.iter())
+            .map(|entry| entry.label.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut lines: Vec<Spans<'static>> = vec![];
+        for section in self.sections.iter() {
+            if !lines.is_empty() {
+                lines.push(Spans::from(""));
+            }
+            lines.push(Spans::from(Span::styled(
+                section.title,
+                title_style.add_modifier(Modifier::BOLD),
+            )));
+            for entry in section.entries.iter() {
+                lines.push(Spans::from(format!(
+                    "  {:width$}  {}",
+                    entry.label,
+                    entry.description,
+                    width = label_width
+                )));
+            }
+        }
+        lines
+    }
+
+    fn get_last_scroll(&self) -> u16 {
+        (self.get_lines(Style::default()).len() as u16).saturating_sub(self.visible_lines.get())
+    }
+
+    pub fn scroll_by(&mut self, lines: i32) {
+        let scroll = (self.scroll as i32 + lines).max(0) as u16;
+        self.scroll = std::cmp::min(scroll, self.get_last_scroll());
+    }
+}
+
+impl Completable for PopupHelpWindow {
+    fn is_completed(&self) -> bool {
+        self.is_done
+    }
+
+    fn reset_completion(&mut self) {
+        self.is_done = false;
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    fn set_active(&mut self, new_active: bool) {
+        self.is_active = new_active;
+    }
+}
+
+#[derive(Clone, Copy)]
+enum HelpAction {
+    LineUp,
+    LineDown,
+    PageUp,
+    PageDown,
+    Close,
+}
+
+static HELP_BINDINGS: &[KeyBinding<HelpAction>] = &[
+    KeyBinding::new(&[KeyCode::Up], "Up", "Scroll up", HelpAction::LineUp),
+    KeyBinding::new(
+        &[KeyCode::Down],
+        "Down",
+        "Scroll down",
+        HelpAction::LineDown,
+    ),
+    KeyBinding::new(
+        &[KeyCode::PageUp],
+        "PgUp",
+        "Previous page",
+        HelpAction::PageUp,
+    ),
+    KeyBinding::new(
+        &[KeyCode::PageDown],
+        "PgDn",
+        "Next page",
+        HelpAction::PageDown,
+    ),
+    KeyBinding::new(
+        &[
+            KeyCode::Esc,
+            KeyCode::Enter,
+            KeyCode::Char('?'),
+            KeyCode::Char('q'),
+            KeyCode::F(1),
+        ],
+        "Esc, ?, Q",
+        "Close the help",
+        HelpAction::Close,
+    ),
+];
+
+impl InputReceptor for PopupHelpWindow {
+    fn handle_input_key(&mut self, key_code: KeyCode) {
+        let page = std::cmp::max(self.visible_lines.get(), 1) as i32;
+        match find_action(HELP_BINDINGS, key_code) {
+            Some(HelpAction::LineUp) => self.scroll_by(-1),
+            Some(HelpAction::LineDown) => self.scroll_by(1),
+            Some(HelpAction::PageUp) => self.scroll_by(-page),
+            Some(HelpAction::PageDown) => self.scroll_by(page),
+            Some(HelpAction::Close) => self.is_done = true,
+            None => {}
+        }
+    }
+
+    fn get_controls_description(&self) -> String {
+        describe_controls(HELP_BINDINGS)
+    }
+
+    fn get_input_mode(&self) -> InputMode {
+        InputMode::CommandMode
+    }
+
+    fn get_help_sections(&self) -> Vec<HelpSection> {
+        vec![HelpSection::from_bindings("Help", HELP_BINDINGS)]
+    }
+}
+
+impl Drawable for PopupHelpWindow {
+    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect, theme: &Theme) {
+        let popup_layout = self.centered_rect(80, 90, layout);
+        frame.render_widget(Clear, popup_layout);
+        self.visible_lines
+            .set(popup_layout.height.saturating_sub(2));
+        let scroll = std::cmp::min(self.scroll, self.get_last_scroll());
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.accent_style())
+            .title("Help (Up/Down, PgUp/PgDn: Scroll | Esc: Close)");
+        let help_paragraph = Paragraph::new(Text::from(self.get_lines(theme.accent_style())))
+            .block(block)
+            .scroll((scroll, 0));
+        frame.render_widget(help_paragraph, popup_layout);
+    }
+}
+
+// PopupCommandPalette
+
+/// How well the query matches the text: its characters have to appear in order, matches
+/// following each other or starting a word scoring more. `None` when it does not match.
+fn get_fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+    for query_char in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (position..text.len()).find(|index| text[*index] == query_char)?;
+        score += 1;
+        if previous_match.map(|previous| previous + 1) == Some(found) {
+            score += 4;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 2;
+        }
+        previous_match = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+#[derive(Clone, Default)]
+struct PaletteEntry {
+    section: &'static str,
+    entry: HelpEntry,
+}
+
+#[derive(Default)]
+pub struct PopupCommandPalette {
+    entries: Vec<PaletteEntry>,
+    query: String,
+    matches: DisplayList<PaletteEntry>,
+    is_active: bool,
+    is_done: bool,
+}
+
+impl PopupCommandPalette {
+    /// A palette of the entries of the sections that can be run.
+    pub fn new(sections: Vec<HelpSection>) -> PopupCommandPalette {
+        let mut palette = PopupCommandPalette {
+            entries: sections
+                .iter()
+                .flat_map(|section| {
+                    section.entries.iter().map(move |entry| PaletteEntry {
+                        section: section.title,
+                        entry: entry.clone(),
+                    })
+                })
+                .filter(|palette_entry| palette_entry.entry.key.is_some())
+                .collect(),
+            is_active: true,
+            ..PopupCommandPalette::default()
+        };
+        palette.update_matches();
+        palette
+    }
+
+    fn update_matches(&mut self) {
+        let mut scored: Vec<(i32, &PaletteEntry)> = self
+            .entries
+            .iter()
+            .filter_map(|palette_entry| {
+                let text = format!(
+                    "{} {}",
+                    palette_entry.entry.description, palette_entry.section
+                );
+                get_fuzzy_score(self.query.as_str(), text.as_str())
+                    .map(|score| (score, palette_entry))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.matches = DisplayList::from(
+            scored
+                .into_iter()
+                .map(|(_, palette_entry)| palette_entry.clone())
+                .collect(),
+        );
+    }
+
+    pub fn push_query(&mut self, text: &str) {
+        self.query.push_str(text);
+        self.update_matches();
+    }
+
+    pub fn select_next(&mut self) {
+        self.matches.next();
+    }
+
+    pub fn select_previous(&mut self) {
+        self.matches.previous();
+    }
+
+    /// The key of the chosen command, once the palette is completed.
+    pub fn get_chosen_key(&self) -> Option<KeyCode> {
+        let selected = self.matches.state.selected()?;
+        self.matches.array.get(selected)?.entry.key
+    }
+
+    /// Selects the command clicked, completing the palette.
+    pub fn click(&mut self, column: u16, row: u16) {
+        if let Some(index) = self.matches.get_index_at(column, row) {
+            self.matches.state.select(Some(index));
+            self.is_done = true;
+        }
+    }
+}
+
+impl Completable for PopupCommandPalette {
+    fn is_completed(&self) -> bool {
+        self.is_done
+    }
+
+    fn reset_completion(&mut self) {
+        self.is_done = false;
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    fn set_active(&mut self, new_active: bool) {
+        self.is_active = new_active;
+    }
+}
+
+#[derive(Clone, Copy)]
+enum PaletteAction {
+    Previous,
+    Next,
+    Run,
+    Close,
+    Erase,
+}
+
+static PALETTE_BINDINGS: &[KeyBinding<PaletteAction>] = &[
+    KeyBinding::new(
+        &[KeyCode::Up],
+        "Up",
+        "Previous command",
+        PaletteAction::Previous,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Down],
+        "Down",
+        "Next command",
+        PaletteAction::Next,
+    ),
+    KeyBinding::new(
+        &[KeyCode::Enter],
+        "Enter",
+        "Run command",
+        PaletteAction::Run,
+    ),
+    KeyBinding::new(&[KeyCode::Esc], "Esc", "Close", PaletteAction::Close),
+    KeyBinding::new(
+        &[KeyCode::Backspace],
+        "Backspace",
+        "Erase the last character",
+        PaletteAction::Erase,
+    ),
+];
+
+impl InputReceptor for PopupCommandPalette {
+    fn handle_input_key(&mut self, key_code: KeyCode) {
+        match (find_action(PALETTE_BINDINGS, key_code), key_code) {
+            (Some(PaletteAction::Previous), _) => self.select_previous(),
+            (Some(PaletteAction::Next), _) => self.select_next(),
+            (Some(PaletteAction::Run), _) => self.is_done = true,
+            (Some(PaletteAction::Close), _) => self.set_active(false),
+            (Some(PaletteAction::Erase), _) => {
+                self.query.pop();
+                self.update_matches();
+            }
+            (None, KeyCode::Char(char)) => self.push_query(char.to_string().as_str()),
+            _ => {}
+        }
+    }
+
+    fn get_controls_description(&self) -> String {
+        describe_controls(PALETTE_BINDINGS)
     }
 
     fn get_input_mode(&self) -> InputMode {
         InputMode::CommandMode
     }
+
+    fn get_help_sections(&self) -> Vec<HelpSection> {
+        let mut section = HelpSection::from_bindings("Command palette", PALETTE_BINDINGS);
+        section.entries.push(HelpEntry::describe_only(
+            "Other keys",
+            "Typed in the search",
+        ));
+        vec![section]
+    }
+}
+
+impl Drawable for PopupCommandPalette {
+    fn display(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, layout: Rect, theme: &Theme) {
+        let popup_layout = self.centered_rect(70, 70, layout);
+        frame.render_widget(Clear, popup_layout);
+        let palette_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(popup_layout);
+        let query_paragraph = Paragraph::new(Text::from(format!("> {}", self.query))).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Command palette"),
+        );
+        frame.render_widget(query_paragraph, palette_layout[0]);
+        let description_width = self
+            .matches
+            .array
+            .iter()
+            .map(|palette_entry| palette_entry.entry.description.chars().count())
+            .max()
+            .unwrap_or(0);
+        let items: Vec<ListItem> = self
+            .matches
+            .array
+            .iter()
+            .map(|palette_entry| {
+                ListItem::new(Text::from(format!(
+                    "{:width$}  {} ({})",
+                    palette_entry.entry.description,
+                    palette_entry.entry.label,
+                    palette_entry.section,
+                    width = description_width
+                )))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL))
+            .highlight_style(theme.selection_style())
+            .highlight_symbol("-> ");
+        frame.render_stateful_widget(list, palette_layout[1], &mut self.matches.state.clone());
+        self.matches.set_drawn(
+            palette_layout[1],
+            &vec![1; self.matches.array.len()],
+            self.matches.state.selected(),
+        );
+    }
+}
+
+#[test]
+fn palette_matches_commands_fuzzily() {
+    assert!(get_fuzzy_score("xpt", "Export project").is_some());
+    assert!(get_fuzzy_score("tpx", "Export project").is_none());
+    assert!(get_fuzzy_score("exp", "Export project") > get_fuzzy_score("exp", "Edit Project"));
+    let sections = vec![
+        HelpSection::from_bindings("Help", HELP_BINDINGS),
+        HelpSection::from_bindings("Text input", TEXT_INPUT_BINDINGS),
+    ];
+    let mut palette = PopupCommandPalette::new(sections);
+    palette.push_query("erase");
+    assert_eq!(palette.get_chosen_key(), Some(KeyCode::Backspace));
+    palette.push_query("zzz");
+    assert_eq!(palette.get_chosen_key(), None);
+}
+
+#[test]
+fn list_items_are_found_where_they_were_drawn() {
+    let list = DisplayList::from(vec!["one", "two", "three", "four"]);
+    // Four rows inside the borders: the second item takes two of them.
+    list.set_drawn(Rect::new(10, 5, 20, 6), &[1, 2, 1, 1], Some(0));
+    assert_eq!(list.get_index_at(12, 6), Some(0));
+    assert_eq!(list.get_index_at(12, 7), Some(1));
+    assert_eq!(list.get_index_at(12, 8), Some(1));
+    assert_eq!(list.get_index_at(12, 9), Some(2));
+    assert_eq!(list.get_index_at(12, 5), None);
+    assert_eq!(list.get_index_at(35, 6), None);
+    // Selecting the last item scrolls the first one out.
+    list.set_drawn(Rect::new(10, 5, 20, 6), &[1, 2, 1, 1], Some(3));
+    assert_eq!(list.get_index_at(12, 6), Some(1));
+    assert_eq!(list.get_index_at(12, 9), Some(3));
 }
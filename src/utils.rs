@@ -3,12 +3,12 @@ use std::ops::Add;
 use std::path::{Path, PathBuf};
 
 use crate::structure::{Project, Task, TaskContainer};
+use chrono::{DateTime, Utc};
 use std::io::Error;
 
-pub fn create_working_folder_if_not_exist() {
-    let working_folder = get_working_folder();
+pub fn create_working_folder_if_not_exist(working_folder: &Path) {
     if !working_folder.exists() {
-        match std::fs::create_dir(working_folder.as_path()) {
+        match std::fs::create_dir(working_folder) {
             Ok(_) => {}
             Err(e) => {
                 panic!("Error occurred while creating the working dir: {}", e);
@@ -36,9 +36,9 @@ pub fn wrap(string_to_wrap: String, wrap_at_length: u32) -> String {
     final_string
 }
 
-pub fn get_working_folder() -> PathBuf {
-    let work_path = match std::env::args().nth(1) {
-        Some(val) => std::path::PathBuf::from(val),
+pub fn get_working_folder(base_path: Option<PathBuf>) -> PathBuf {
+    let work_path = match base_path {
+        Some(val) => val,
         None => dirs::home_dir().unwrap(),
     };
     let folder_path = String::from('.').add(PROJECT_FILE_EXTENSION);
@@ -46,41 +46,56 @@ pub fn get_working_folder() -> PathBuf {
 }
 
 pub fn delete_project_of_name(project_name: String, working_path: PathBuf) -> Result<(), Error> {
-    let mut path = working_path.join(project_name);
-    path.set_extension(PROJECT_FILE_EXTENSION);
+    let path = match find_project_path(working_path.as_path(), project_name.as_str()) {
+        Some(path) => path,
+        None => working_path
+            .join(project_name)
+            .with_extension(PROJECT_FILE_EXTENSION),
+    };
     match std::fs::remove_file(path.as_path()) {
         Ok(()) => Ok(()),
         Err(e) => Result::Err(e),
     }
 }
 
+/// Reads a project file, the timestamps it predates taking the modification time of the file.
 pub fn load_project_from_path(path: PathBuf) -> Result<Project, std::io::Error> {
-    match std::fs::read_to_string(path) {
+    let mut project: Project = match std::fs::read_to_string(path.as_path()) {
         Ok(project_string) => match serde_json::from_str(project_string.as_str()) {
-            Ok(deserialized_project) => Result::Ok(deserialized_project),
-            Err(e) => Result::Err(Error::from(e)),
+            Ok(deserialized_project) => deserialized_project,
+            Err(e) => return Result::Err(Error::from(e)),
         },
-        Err(e) => Result::Err(e),
-    }
+        Err(e) => return Result::Err(e),
+    };
+    let saved_at = std::fs::metadata(path.as_path())?.modified()?;
+    project.fill_missing_timestamps(DateTime::<Utc>::from(saved_at));
+    Ok(project)
+}
+
+fn is_group_folder(entry: &std::fs::DirEntry) -> bool {
+    entry.file_type().map(|t| t.is_dir()).unwrap_or(false)
+        && !entry.file_name().to_string_lossy().starts_with('.')
 }
 
+/// Loads the projects of the folder and of its sub-folders, those of a sub-folder knowing its
+/// name as their folder group.
 pub fn get_projects_in_path(path: PathBuf) -> Vec<Project> {
     let mut serialized_projects: Vec<Project> = vec![];
     let folder_result = std::fs::read_dir(path.as_path()).unwrap();
     for file in folder_result {
         let f = file.unwrap();
-        if f.file_type().unwrap().is_file() {
+        if is_group_folder(&f) {
+            let group = f.file_name().to_string_lossy().to_string();
+            for mut project in get_projects_in_path(f.path()) {
+                project.folder_group.get_or_insert_with(|| group.clone());
+                serialized_projects.push(project);
+            }
+        } else if f.file_type().unwrap().is_file() {
             match f.path().extension() {
                 Some(ext) => {
                     if ext == PROJECT_FILE_EXTENSION {
-                        match match serde_json::from_str(
-                            std::fs::read_to_string(f.path()).unwrap().as_str(),
-                        ) {
-                            Ok(result) => Some(result),
-                            Err(_) => None,
-                        } {
-                            Some(project) => serialized_projects.push(project),
-                            _ => {}
+                        if let Ok(project) = load_project_from_path(f.path()) {
+                            serialized_projects.push(project);
                         }
                     }
                 }
@@ -91,6 +106,20 @@ pub fn get_projects_in_path(path: PathBuf) -> Vec<Project> {
     serialized_projects
 }
 
+/// Path of the file of the project, looked for in the folder then in its group folders.
+pub fn find_project_path(folder: &Path, project_name: &str) -> Option<PathBuf> {
+    let mut path = folder.join(project_name);
+    path.set_extension(PROJECT_FILE_EXTENSION);
+    if path.is_file() {
+        return Some(path);
+    }
+    std::fs::read_dir(folder)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(is_group_folder)
+        .find_map(|entry| find_project_path(entry.path().as_path(), project_name))
+}
+
 #[test]
 fn create_dummy_project() {
     create_dummy_project_with_name(String::from("project1"));
@@ -99,7 +128,7 @@ fn create_dummy_project() {
 }
 #[allow(dead_code)]
 fn create_dummy_project_with_name(name: String) {
-    create_working_folder_if_not_exist();
+    create_working_folder_if_not_exist(&get_working_folder(None));
     let mut p = Project::new(name.clone());
     p.description = p.name.clone().add(" description");
     p.add_task(
@@ -114,7 +143,7 @@ fn create_dummy_project_with_name(name: String) {
         String::from("a completed task"),
         String::from("Sample description"),
     ));
-    let mut project_file_path = get_working_folder().join(p.name.clone());
+    let mut project_file_path = get_working_folder(None).join(p.name.clone());
     project_file_path.set_extension(PROJECT_FILE_EXTENSION);
     match p.write_project_full_path(project_file_path) {
         Ok(_) => {}